@@ -1,50 +1,31 @@
-use kwui::{IntoScriptValue, ScriptEngine, ScriptEventHandler, ScriptValue};
+use kwui::{
+    Application, GlobalFunctionHandle, IntoScriptValue, ScriptEngine, ScriptEventHandler,
+    ScriptValue, TimerHandle,
+};
 use std::cell::RefCell;
+use std::time::Duration;
 
+#[derive(IntoScriptValue)]
+#[script(rename_all = "camelCase")]
 struct Product {
     display_name: &'static str,
     version: &'static str,
 }
 
-impl IntoScriptValue for Product {
-    fn into_script_value(self) -> Result<ScriptValue, ()> {
-        let mut obj = ScriptValue::new_object();
-        obj.set_by_str("displayName", self.display_name);
-        obj.set_by_str("version", self.version);
-        Ok(obj)
-    }
-}
-
+#[derive(IntoScriptValue)]
+#[script(as_array)]
 struct TargetDir {
     dir: String,
     valid: bool,
 }
 
-impl IntoScriptValue for TargetDir {
-    fn into_script_value(self) -> Result<ScriptValue, ()> {
-        let mut obj = ScriptValue::new_array();
-        obj.set_by_index(0, self.dir);
-        obj.set_by_index(1, self.valid);
-        Ok(obj)
-    }
-}
-
+#[derive(IntoScriptValue)]
+#[script(as_array)]
 struct FreeSpace {
     estimated_size_mb: usize,
     target_free_space_gb: Option<usize>,
 }
 
-impl IntoScriptValue for FreeSpace {
-    fn into_script_value(self) -> Result<ScriptValue, ()> {
-        let mut obj = ScriptValue::new_array();
-        obj.set_by_index(0, self.estimated_size_mb);
-        if let Some(space) = self.target_free_space_gb {
-            obj.set_by_index(1, space);
-        }
-        Ok(obj)
-    }
-}
-
 pub struct Model;
 
 #[derive(Default)]
@@ -54,10 +35,11 @@ struct ModelState {
     current_page: &'static str,
     main_page_expanded: bool,
     install_progress: f64,
+    progress_timer: Option<TimerHandle>,
+    global_functions: Vec<GlobalFunctionHandle>,
     expand_button_clicked_handler: Option<ScriptEventHandler>,
     start_button_clicked_handler: Option<ScriptEventHandler>,
     done_button_clicked_handler: Option<ScriptEventHandler>,
-    animation_event_handler: Option<ScriptEventHandler>,
     request_close_handler: Option<ScriptEventHandler>,
     enter_key_down_handler: Option<ScriptEventHandler>,
 
@@ -86,13 +68,20 @@ impl ModelState {
 }
 impl Model {
     pub fn init() {
-        ScriptEngine::add_global_function("getCurrentPage", Model::get_current_page);
-        ScriptEngine::add_global_function("getTargetDir", Model::get_target_dir);
-        ScriptEngine::add_global_function("getFreeSpace", Model::get_free_space);
-        ScriptEngine::add_global_function("isMainPageExpanded", Model::is_main_page_expanded);
-        ScriptEngine::add_global_function("getInstallProgress", Model::get_install_progress);
-
         MODEL.with_borrow_mut(|m| {
+            m.global_functions = vec![
+                ScriptEngine::add_global_function("getCurrentPage", Model::get_current_page),
+                ScriptEngine::add_global_function("getTargetDir", Model::get_target_dir),
+                ScriptEngine::add_global_function("getFreeSpace", Model::get_free_space),
+                ScriptEngine::add_global_function(
+                    "isMainPageExpanded",
+                    Model::is_main_page_expanded,
+                ),
+                ScriptEngine::add_global_function(
+                    "getInstallProgress",
+                    Model::get_install_progress,
+                ),
+            ];
             m.expand_button_clicked_handler = ScriptEngine::add_event_listener(
                 "install-dialog:expand-button-clicked",
                 Model::on_expand_button_clicked,
@@ -108,11 +97,6 @@ impl Model {
                 Model::on_done_button_clicked,
             )
             .into();
-            m.animation_event_handler = ScriptEngine::add_event_listener(
-                "dialog:animation-event",
-                Model::on_animation_event,
-            )
-            .into();
             m.request_close_handler =
                 ScriptEngine::add_event_listener("dialog:request-close", Model::on_request_close)
                     .into();
@@ -158,13 +142,20 @@ impl Model {
         ScriptEngine::post_event0("install-dialog:main-page-expanded");
     }
     fn on_start_button_clicked() {
-        MODEL.with_borrow_mut(|m| m.current_page = "progress");
+        MODEL.with_borrow_mut(|m| {
+            m.current_page = "progress";
+            m.progress_timer = Application::set_interval(
+                Duration::from_millis(1000 / 60),
+                Model::on_progress_timer,
+            )
+            .into();
+        });
         ScriptEngine::post_event0("install-dialog:current-page-changed");
     }
     fn on_done_button_clicked() {
         Model::close_main_dialog();
     }
-    fn on_animation_event() {
+    fn on_progress_timer() {
         let (new_progress, notify) = MODEL.with_borrow_mut(|m| -> (f64, bool) {
             if m.current_page == "progress" {
                 if m.install_progress < 1.0 {
@@ -186,7 +177,10 @@ impl Model {
         if notify {
             ScriptEngine::post_event1("install-dialog:progress-changed", new_progress);
             if new_progress >= 1.0 {
-                MODEL.with_borrow_mut(|m| m.current_page = "done");
+                MODEL.with_borrow_mut(|m| {
+                    m.current_page = "done";
+                    m.progress_timer = None;
+                });
                 ScriptEngine::post_event0("install-dialog:current-page-changed");
             }
         }
@@ -230,7 +224,7 @@ impl Model {
     }
     fn on_confirm_dialog_cancel_button_clicked() {
         Model::close_confirm_dialog();
-        Model::close_main_dialog();        
+        Model::close_main_dialog();
     }
     fn close_confirm_dialog() {
         let dialog_id: String = MODEL.with_borrow_mut(|m| {
@@ -4,45 +4,32 @@ use hyper::{body::Buf, Request};
 use hyper::{service, Response};
 use hyper::{Body, Client};
 use hyper_boring::HttpsConnector;
-use kwui::{IntoScriptValue, ScriptEngine, ScriptValue};
+use kwui::{
+    Application, GlobalFunctionHandle, IntoScriptValue, ScriptEngine, ScriptError, ScriptValue,
+};
 use rss;
+use serde::Serialize;
 use std::cell::RefCell;
 use tokio;
 use tokio::net::TcpStream;
 
 const FEED_URL: &str = "https://www.vgtime.com/rss.jhtml";
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 struct Channel {
     title: String,
     items: Vec<ChannelItem>,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 struct ChannelItem {
     title: String,
     description: String,
 }
 
 impl IntoScriptValue for Channel {
-    fn into_script_value(self) -> Result<ScriptValue, ()> {
-        let mut obj = ScriptValue::new_object();
-        obj.set_by_str("title", self.title);
-        let mut items = ScriptValue::new_array();
-        for (i, v) in self.items.into_iter().enumerate() {
-            items.set_by_index(i, v);
-        }
-        obj.set_value_by_str("items", items);
-        Ok(obj)
-    }
-}
-
-impl IntoScriptValue for ChannelItem {
-    fn into_script_value(self) -> Result<ScriptValue, ()> {
-        let mut obj = ScriptValue::new_object();
-        obj.set_by_str("title", self.title);
-        obj.set_by_str("description", self.description);
-        Ok(obj)
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
+        kwui::to_script_value(&self)
     }
 }
 
@@ -51,36 +38,25 @@ pub struct Model;
 #[derive(Default)]
 struct ModelState {
     channel: Option<Channel>,
-    rt: Option<tokio::runtime::Runtime>,
+    global_functions: Vec<GlobalFunctionHandle>,
 }
 
 thread_local! {
-    static MODEL: RefCell<ModelState> = RefCell::new(ModelState::new());
-}
-
-impl ModelState {
-    fn new() -> Self {
-        let rt = tokio::runtime::Builder::new_multi_thread()
-            .enable_all()
-            .build()
-            .unwrap();
-        Self {
-            channel: None,
-            rt: rt.into(),
-        }
-    }
+    static MODEL: RefCell<ModelState> = RefCell::new(ModelState::default());
 }
 
 impl Model {
     pub fn init() {
-        ScriptEngine::add_global_function("getChannel", Model::get_channel);
-        ScriptEngine::add_global_function("reloadChannel", Model::reload_channel);
-    }
-    fn reload_channel() {
         MODEL.with_borrow_mut(|m| {
-            m.channel = None;
-            m.rt.as_ref().unwrap().spawn(Model::do_load_channel());
+            m.global_functions = vec![
+                ScriptEngine::add_global_function("getChannel", Model::get_channel),
+                ScriptEngine::add_global_function("reloadChannel", Model::reload_channel),
+            ];
         });
+    }
+    fn reload_channel() {
+        MODEL.with_borrow_mut(|m| m.channel = None);
+        Application::spawn(Model::do_load_channel());
         ScriptEngine::post_event1("main-dialog:channel-loaded", Channel::default());
     }
     fn get_channel() -> Channel {
@@ -134,7 +110,7 @@ impl Model {
                 .collect(),
         };
         eprintln!("loaded channel items.len={}", chan.items.len());
-        kwui::Application::run_in_main_thread(move || {
+        Application::main_thread_handle().run(move || {
             Model::on_channel_loaded(chan);
         });
         Ok(())
@@ -0,0 +1,159 @@
+use heck::{
+    ToKebabCase, ToLowerCamelCase, ToShoutyKebabCase, ToShoutySnakeCase, ToSnakeCase,
+    ToUpperCamelCase,
+};
+use syn::{Attribute, LitStr, Result};
+
+/// Naming convention of `#[script(rename_all = "...")]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn from_str(s: &str) -> Option<Self> {
+        let rule = match s {
+            "lowercase" => RenameRule::Lower,
+            "UPPERCASE" => RenameRule::Upper,
+            "PascalCase" => RenameRule::Pascal,
+            "camelCase" => RenameRule::Camel,
+            "snake_case" => RenameRule::Snake,
+            "SCREAMING_SNAKE_CASE" => RenameRule::ScreamingSnake,
+            "kebab-case" => RenameRule::Kebab,
+            "SCREAMING-KEBAB-CASE" => RenameRule::ScreamingKebab,
+            _ => return None,
+        };
+        Some(rule)
+    }
+
+    pub fn apply(self, name: &str) -> String {
+        match self {
+            RenameRule::Lower => name.to_snake_case().replace('_', ""),
+            RenameRule::Upper => name.to_shouty_snake_case().replace('_', ""),
+            RenameRule::Pascal => name.to_upper_camel_case(),
+            RenameRule::Camel => name.to_lower_camel_case(),
+            RenameRule::Snake => name.to_snake_case(),
+            RenameRule::ScreamingSnake => name.to_shouty_snake_case(),
+            RenameRule::Kebab => name.to_kebab_case(),
+            RenameRule::ScreamingKebab => name.to_shouty_kebab_case(),
+        }
+    }
+}
+
+/// `#[script(...)]` on struct or enum.
+#[derive(Default)]
+pub struct ContainerAttrs {
+    pub rename_all: Option<RenameRule>,
+    pub as_array: bool,
+}
+
+/// `#[script(...)]` on field.
+#[derive(Default)]
+pub struct FieldAttrs {
+    pub rename: Option<String>,
+    pub skip: bool,
+    pub default: bool,
+}
+
+/// `#[script(...)]` on enum variant.
+#[derive(Default)]
+pub struct VariantAttrs {
+    pub rename: Option<String>,
+}
+
+fn script_attrs(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attrs.iter().filter(|a| a.path().is_ident("script"))
+}
+
+impl ContainerAttrs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut ret = Self::default();
+        for attr in script_attrs(attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename_all") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    ret.rename_all = Some(
+                        RenameRule::from_str(&s.value())
+                            .ok_or_else(|| syn::Error::new(s.span(), "unknown rename_all rule"))?,
+                    );
+                    Ok(())
+                } else if meta.path.is_ident("as_array") {
+                    ret.as_array = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported script attribute"))
+                }
+            })?;
+        }
+        Ok(ret)
+    }
+}
+
+impl FieldAttrs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut ret = Self::default();
+        for attr in script_attrs(attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    ret.rename = Some(s.value());
+                    Ok(())
+                } else if meta.path.is_ident("skip") {
+                    ret.skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("default") {
+                    ret.default = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported script attribute"))
+                }
+            })?;
+        }
+        Ok(ret)
+    }
+}
+
+impl VariantAttrs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut ret = Self::default();
+        for attr in script_attrs(attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    ret.rename = Some(s.value());
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported script attribute"))
+                }
+            })?;
+        }
+        Ok(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rename_rules() {
+        assert_eq!(RenameRule::Camel.apply("display_name"), "displayName");
+        assert_eq!(RenameRule::Pascal.apply("display_name"), "DisplayName");
+        assert_eq!(RenameRule::Kebab.apply("display_name"), "display-name");
+        assert_eq!(
+            RenameRule::ScreamingSnake.apply("display_name"),
+            "DISPLAY_NAME"
+        );
+        assert_eq!(RenameRule::Lower.apply("display_name"), "displayname");
+        assert_eq!(RenameRule::Snake.apply("TargetDir"), "target_dir");
+        assert_eq!(RenameRule::Camel.apply("TargetDir"), "targetDir");
+        assert_eq!(RenameRule::Upper.apply("TargetDir"), "TARGETDIR");
+    }
+}
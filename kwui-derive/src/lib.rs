@@ -0,0 +1,422 @@
+//! Derive macros for [kwui](https://docs.rs/kwui), re-exported by the `derive` feature of `kwui`.
+//!
+//! ```ignore
+//! use kwui::{FromScriptValue, IntoScriptValue};
+//!
+//! #[derive(IntoScriptValue, FromScriptValue)]
+//! #[script(rename_all = "camelCase")]
+//! struct Product {
+//!     display_name: String,
+//!     version: String,
+//! }
+//!
+//! #[derive(IntoScriptValue)]
+//! #[script(as_array)]
+//! struct TargetDir {
+//!     dir: String,
+//!     valid: bool,
+//! }
+//! ```
+//!
+//! Structs become objects, tuple structs become arrays, newtype structs become the inner value.
+//! Unit enum variants become strings, other variants become `{ "Variant": payload }` objects.
+//!
+//! Container attributes:
+//! - `#[script(rename_all = "...")]`: rename fields and variants, one of `lowercase`,
+//!   `UPPERCASE`, `PascalCase`, `camelCase`, `snake_case`, `SCREAMING_SNAKE_CASE`,
+//!   `kebab-case`, `SCREAMING-KEBAB-CASE`.
+//! - `#[script(as_array)]`: encode struct fields as an array, in declaration order.
+//!
+//! Field attributes:
+//! - `#[script(rename = "name")]`: use `name` as the property name.
+//! - `#[script(skip)]`: never encoded, decoded as `Default::default()`.
+//! - `#[script(default)]`: decode as `Default::default()` when the property is missing or `null`.
+//!
+//! Variant attributes:
+//! - `#[script(rename = "name")]`: use `name` as the variant tag.
+
+mod attr;
+
+use attr::{ContainerAttrs, FieldAttrs, RenameRule, VariantAttrs};
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Generics, Result};
+
+/// Derive `kwui::IntoScriptValue`.
+#[proc_macro_derive(IntoScriptValue, attributes(script))]
+pub fn derive_into_script_value(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_into(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `kwui::FromScriptValue`.
+#[proc_macro_derive(FromScriptValue, attributes(script))]
+pub fn derive_from_script_value(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_from(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+struct Field {
+    member: syn::Member,
+    binding: syn::Ident,
+    key: String,
+    attrs: FieldAttrs,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Style {
+    Named,
+    Unnamed,
+    Unit,
+}
+
+fn collect_fields(fields: &Fields, rename_all: Option<RenameRule>) -> Result<(Style, Vec<Field>)> {
+    let style = match fields {
+        Fields::Named(_) => Style::Named,
+        Fields::Unnamed(_) => Style::Unnamed,
+        Fields::Unit => Style::Unit,
+    };
+    let fields = fields
+        .iter()
+        .enumerate()
+        .map(|(i, f)| {
+            let attrs = FieldAttrs::parse(&f.attrs)?;
+            let (member, name) = match &f.ident {
+                Some(ident) => {
+                    let name = ident.to_string();
+                    let name = name.strip_prefix("r#").unwrap_or(&name).to_string();
+                    (syn::Member::Named(ident.clone()), name)
+                }
+                None => (syn::Member::Unnamed(i.into()), i.to_string()),
+            };
+            let binding = format_ident!("__field{}", i);
+            let key = match (&attrs.rename, rename_all) {
+                (Some(rename), _) => rename.clone(),
+                (None, Some(rule)) => rule.apply(&name),
+                (None, None) => name,
+            };
+            Ok(Field {
+                member,
+                binding,
+                key,
+                attrs,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok((style, fields))
+}
+
+fn variant_tag(variant: &syn::Variant, rename_all: Option<RenameRule>) -> Result<String> {
+    let attrs = VariantAttrs::parse(&variant.attrs)?;
+    let name = variant.ident.to_string();
+    Ok(match (attrs.rename, rename_all) {
+        (Some(rename), _) => rename,
+        (None, Some(rule)) => rule.apply(&name),
+        (None, None) => name,
+    })
+}
+
+fn add_bounds(generics: &Generics, bound: TokenStream2) -> Generics {
+    let mut generics = generics.clone();
+    let params = generics
+        .type_params()
+        .map(|p| p.ident.clone())
+        .collect::<Vec<_>>();
+    let where_clause = generics.make_where_clause();
+    for ident in params {
+        where_clause
+            .predicates
+            .push(syn::parse_quote!(#ident: #bound));
+    }
+    generics
+}
+
+/// Encode bound fields as object, array or the single inner value.
+fn into_fields(style: Style, fields: &[Field], as_array: bool) -> TokenStream2 {
+    let fields = fields.iter().filter(|f| !f.attrs.skip).collect::<Vec<_>>();
+    if style == Style::Unit {
+        return quote!(::kwui::ScriptValue::new_null());
+    }
+    if style == Style::Unnamed && fields.len() == 1 && !as_array {
+        let binding = &fields[0].binding;
+        return quote!(::kwui::IntoScriptValue::into_script_value(#binding)?);
+    }
+    if style == Style::Unnamed || as_array {
+        let sets = fields.iter().enumerate().map(|(i, f)| {
+            let binding = &f.binding;
+            quote! {
+                arr.set_value_by_index(
+                    #i,
+                    ::kwui::IntoScriptValue::into_script_value(#binding)
+                        .map_err(|e| e.at_index(#i))?,
+                );
+            }
+        });
+        quote! {{
+            let arr = ::kwui::ScriptValue::new_array();
+            #(#sets)*
+            arr
+        }}
+    } else {
+        let sets = fields.iter().map(|f| {
+            let binding = &f.binding;
+            let key = &f.key;
+            quote! {
+                obj.set_value_by_str(
+                    #key,
+                    ::kwui::IntoScriptValue::into_script_value(#binding)
+                        .map_err(|e| e.at_key(#key))?,
+                );
+            }
+        });
+        quote! {{
+            let mut obj = ::kwui::ScriptValue::new_object();
+            #(#sets)*
+            obj
+        }}
+    }
+}
+
+/// Pattern destructuring all fields into their bindings.
+fn bind_fields(style: Style, fields: &[Field]) -> TokenStream2 {
+    let binds = fields.iter().map(|f| {
+        let member = &f.member;
+        let binding = &f.binding;
+        quote!(#member: #binding)
+    });
+    match style {
+        Style::Unit => quote!(),
+        _ => quote!({ #(#binds),* }),
+    }
+}
+
+fn expand_into(input: &DeriveInput) -> Result<TokenStream2> {
+    let attrs = ContainerAttrs::parse(&input.attrs)?;
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let (style, fields) = collect_fields(&data.fields, attrs.rename_all)?;
+            let bind = bind_fields(style, &fields);
+            let encode = into_fields(style, &fields, attrs.as_array);
+            quote! {
+                #[allow(unused_variables)]
+                let Self #bind = self;
+                let value = #encode;
+                Ok(value)
+            }
+        }
+        Data::Enum(data) => {
+            if attrs.as_array {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    "as_array is only supported on structs",
+                ));
+            }
+            let arms = data
+                .variants
+                .iter()
+                .map(|v| {
+                    let ident = &v.ident;
+                    let tag = variant_tag(v, attrs.rename_all)?;
+                    let (style, fields) = collect_fields(&v.fields, None)?;
+                    let bind = bind_fields(style, &fields);
+                    Ok(if style == Style::Unit {
+                        quote! {
+                            Self::#ident => Ok(::kwui::ScriptValue::new_string(#tag)),
+                        }
+                    } else {
+                        let encode = into_fields(style, &fields, false);
+                        quote! {
+                            #[allow(unused_variables)]
+                            Self::#ident #bind => Ok(::kwui::__private::new_tagged(#tag, #encode)),
+                        }
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "IntoScriptValue can not be derived for unions",
+            ))
+        }
+    };
+
+    let name = &input.ident;
+    let generics = add_bounds(&input.generics, quote!(::kwui::IntoScriptValue));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::kwui::IntoScriptValue for #name #ty_generics #where_clause {
+            fn into_script_value(
+                self,
+            ) -> ::std::result::Result<::kwui::ScriptValue, ::kwui::ScriptError> {
+                #body
+            }
+        }
+    })
+}
+
+/// Decode `value` into `ctor` with fields from object, array or the single inner value.
+fn from_fields(
+    ctor: TokenStream2,
+    style: Style,
+    fields: &[Field],
+    as_array: bool,
+    value: TokenStream2,
+) -> TokenStream2 {
+    if style == Style::Unit {
+        return quote!(#ctor);
+    }
+    let decoded = fields.iter().filter(|f| !f.attrs.skip).count();
+    if style == Style::Unnamed && decoded == 1 && !as_array {
+        let inits = fields.iter().map(|f| {
+            let member = &f.member;
+            if f.attrs.skip {
+                quote!(#member: ::std::default::Default::default())
+            } else {
+                quote!(#member: ::kwui::FromScriptValue::from_script_value(#value)?)
+            }
+        });
+        return quote!(#ctor { #(#inits),* });
+    }
+    let positional = style == Style::Unnamed || as_array;
+    let mut index = 0usize;
+    let inits = fields
+        .iter()
+        .map(|f| {
+            let member = &f.member;
+            if f.attrs.skip {
+                return quote!(#member: ::std::default::Default::default());
+            }
+            let (get, map_err) = if positional {
+                let i = index;
+                index += 1;
+                (
+                    quote!(#value.get_value_by_index(#i)),
+                    quote!(|e| e.at_index(#i)),
+                )
+            } else {
+                let key = &f.key;
+                (
+                    quote!(#value.get_value_by_str(#key)),
+                    quote!(|e| ::kwui::__private::field_error(e, &v, #key)),
+                )
+            };
+            if f.attrs.default {
+                quote! {
+                    #member: {
+                        let v = #get;
+                        if v.is_null() || v.is_undefined() {
+                            ::std::default::Default::default()
+                        } else {
+                            ::kwui::FromScriptValue::from_script_value(&v).map_err(#map_err)?
+                        }
+                    }
+                }
+            } else {
+                quote! {
+                    #member: {
+                        let v = #get;
+                        ::kwui::FromScriptValue::from_script_value(&v).map_err(#map_err)?
+                    }
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    let (check, expected) = if positional {
+        (quote!(is_array), "array")
+    } else {
+        (quote!(is_object), "object")
+    };
+    quote! {{
+        if !#value.#check() {
+            return Err(::kwui::ScriptError::type_mismatch(#expected, #value));
+        }
+        #ctor { #(#inits),* }
+    }}
+}
+
+fn expand_from(input: &DeriveInput) -> Result<TokenStream2> {
+    let attrs = ContainerAttrs::parse(&input.attrs)?;
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let (style, fields) = collect_fields(&data.fields, attrs.rename_all)?;
+            let decode = from_fields(quote!(Self), style, &fields, attrs.as_array, quote!(value));
+            quote!(Ok(#decode))
+        }
+        Data::Enum(data) => {
+            if attrs.as_array {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    "as_array is only supported on structs",
+                ));
+            }
+            let arms = data
+                .variants
+                .iter()
+                .map(|v| {
+                    let ident = &v.ident;
+                    let tag = variant_tag(v, attrs.rename_all)?;
+                    let (style, fields) = collect_fields(&v.fields, None)?;
+                    Ok(if style == Style::Unit {
+                        quote!(#tag => Ok(Self::#ident),)
+                    } else {
+                        let decode = from_fields(
+                            quote!(Self::#ident),
+                            style,
+                            &fields,
+                            false,
+                            quote!(payload),
+                        );
+                        let missing = format!("variant `{}` expects a payload", tag);
+                        quote! {
+                            #tag => {
+                                let payload = &payload
+                                    .ok_or_else(|| ::kwui::ScriptError::custom(#missing))?;
+                                Ok(#decode)
+                            }
+                        }
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            quote! {
+                #[allow(unused_variables)]
+                let (tag, payload) = ::kwui::__private::enum_variant(value)?;
+                match tag.as_str() {
+                    #(#arms)*
+                    _ => Err(::kwui::ScriptError::custom(format!("unknown variant `{}`", tag))),
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "FromScriptValue can not be derived for unions",
+            ))
+        }
+    };
+
+    let name = &input.ident;
+    let generics = add_bounds(&input.generics, quote!(::kwui::FromScriptValue));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::kwui::FromScriptValue for #name #ty_generics #where_clause {
+            fn from_script_value(
+                value: &::kwui::ScriptValue,
+            ) -> ::std::result::Result<Self, ::kwui::ScriptError> {
+                #body
+            }
+        }
+    })
+}
@@ -0,0 +1,44 @@
+//! Support functions for `kwui-derive` generated code and serde support, not public API.
+
+use crate::script_error::ScriptError;
+use crate::script_value::ScriptValue;
+
+/// Make `{ variant: payload }` object.
+pub fn new_tagged(variant: &str, payload: ScriptValue) -> ScriptValue {
+    let mut obj = ScriptValue::new_object();
+    obj.set_value_by_str(variant, payload);
+    obj
+}
+
+/// Split enum value into variant tag and optional payload.
+///
+/// Accepts `"Variant"` strings and `{ "Variant": payload }` objects.
+pub fn enum_variant(value: &ScriptValue) -> Result<(String, Option<ScriptValue>), ScriptError> {
+    if value.is_string() {
+        return Ok((value.to_string(), None));
+    }
+    if !value.is_object() {
+        return Err(ScriptError::type_mismatch("string or object", value));
+    }
+    let mut keys = Vec::new();
+    value.visit_object(|k, _| keys.push(k.to_string()));
+    if keys.len() != 1 {
+        return Err(ScriptError::custom(
+            "expected object with a single variant key",
+        ));
+    }
+    let tag = keys.pop().unwrap();
+    let payload = value.get_value_by_str(&tag);
+    Ok((tag, Some(payload)))
+}
+
+/// Add property `key` to the error path, reporting absent property as missing.
+pub fn field_error(err: ScriptError, value: &ScriptValue, key: &str) -> ScriptError {
+    let absent = value.is_null() || value.is_undefined();
+    match err {
+        ScriptError::TypeMismatch { ref path, .. } if absent && path.is_empty() => {
+            ScriptError::missing_property(key)
+        }
+        err => err.at_key(key),
+    }
+}
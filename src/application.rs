@@ -1,33 +1,243 @@
+use crate::panic_guard::PanicAction;
 use kwui_sys::*;
 use std::ffi::CString;
+use std::future::Future;
+use std::os::raw::{c_char, c_void};
+use std::sync::RwLock;
 
 /// The kwui running environment.
 pub struct Application {
     inner: *mut kwui_Application,
+    // Keep `argv` alive, the native side may hold pointers into it.
+    _args: Vec<CString>,
+    _argv: Vec<*mut c_char>,
+    #[cfg(feature = "tokio")]
+    runtime: Option<tokio::runtime::Runtime>,
 }
 
 type Closure<'a> = Box<dyn FnOnce() + 'a>;
+type LogCallback = Box<dyn Fn(&str) + Send + Sync>;
+type PanicHook = Box<dyn Fn(&str) -> PanicAction + Send + Sync>;
 
-impl Application {
-    /// Create the kwui Application.
+/// Whether the native application is alive, main thread tasks can be posted.
+static ALIVE: RwLock<bool> = RwLock::new(false);
+
+/// Error returned by `ApplicationBuilder::build`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApplicationError {
+    /// The command line argument at `index` contains an interior nul byte.
+    InvalidArgument { index: usize },
+    /// The resource root directory contains an interior nul byte.
+    InvalidResourceRootDir,
+    /// The native application could not be created.
+    CreateFailed,
+    /// The tokio runtime could not be created.
+    #[cfg(feature = "tokio")]
+    CreateRuntimeFailed,
+}
+
+impl std::fmt::Display for ApplicationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApplicationError::InvalidArgument { index } => {
+                write!(f, "command line argument {} contains a nul byte", index)
+            }
+            ApplicationError::InvalidResourceRootDir => {
+                f.write_str("resource root directory contains a nul byte")
+            }
+            ApplicationError::CreateFailed => f.write_str("create kwui application failed"),
+            #[cfg(feature = "tokio")]
+            ApplicationError::CreateRuntimeFailed => f.write_str("create tokio runtime failed"),
+        }
+    }
+}
+
+impl std::error::Error for ApplicationError {}
+
+enum ResourceRoot {
+    Dir(String),
+    Data(&'static [u8]),
+}
+
+/// Configure and create the kwui `Application`.
+///
+/// Unlike `Application::new`, nothing is read from the process environment,
+/// all options must be given explicitly.
+///
+/// ```no_run
+/// use kwui::ApplicationBuilder;
+///
+/// let app = ApplicationBuilder::new()
+///     .args(["my_app"])
+///     .resource_root_dir("./assets")
+///     .script_reload(false)
+///     .log_callback(|msg| eprintln!("{}", msg))
+///     .build()
+///     .unwrap();
+/// ```
+pub struct ApplicationBuilder {
+    args: Vec<String>,
+    resource_root: Option<ResourceRoot>,
+    script_reload: bool,
+    log_callback: Option<LogCallback>,
+    panic_hook: Option<PanicHook>,
+    #[cfg(feature = "tokio")]
+    runtime: Option<tokio::runtime::Runtime>,
+}
+
+impl ApplicationBuilder {
+    /// Create a builder with empty arguments, no resource root and script reload disabled.
     pub fn new() -> Self {
-        let args = std::env::args()
+        Self {
+            args: Vec::new(),
+            resource_root: None,
+            script_reload: false,
+            log_callback: None,
+            panic_hook: None,
+            #[cfg(feature = "tokio")]
+            runtime: None,
+        }
+    }
+    /// Set the command line arguments, including program name.
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+    /// Set resource directory to local folder
+    pub fn resource_root_dir(mut self, dir: &str) -> Self {
+        self.resource_root = Some(ResourceRoot::Dir(dir.to_string()));
+        self
+    }
+    /// Load resource from packed data
+    ///
+    /// Users can pack resources with kwui-cli: `kwui pack-archive --help`
+    pub fn resource_root_data(mut self, data: &'static [u8]) -> Self {
+        self.resource_root = Some(ResourceRoot::Data(data));
+        self
+    }
+    /// Reload scripts when files under the resource root directory change.
+    pub fn script_reload(mut self, enable: bool) -> Self {
+        self.script_reload = enable;
+        self
+    }
+    /// Receive log messages of the native runtime.
+    ///
+    /// Use `kwui::logging::forward` to route them into the `log` facade.
+    ///
+    /// NOTE: the callback is process wide, the last installed one wins.
+    pub fn log_callback<F: Fn(&str) + Send + Sync + 'static>(mut self, callback: F) -> Self {
+        self.log_callback = Some(Box::new(callback));
+        self
+    }
+    /// Decide what to do when a Rust callback called by kwui panics, with the panic message.
+    ///
+    /// Panics never unwind into the native side. By default they are logged, and
+    /// exported functions return a JavaScript `Error` object with the panic message.
+    ///
+    /// NOTE: the hook is process wide, the last installed one wins.
+    pub fn on_callback_panic<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&str) -> PanicAction + Send + Sync + 'static,
+    {
+        self.panic_hook = Some(Box::new(hook));
+        self
+    }
+    /// Use `runtime` for `Application::spawn`, instead of a default multi-threaded one.
+    ///
+    /// The runtime is shut down when the `Application` is dropped.
+    #[cfg(feature = "tokio")]
+    pub fn tokio_runtime(mut self, runtime: tokio::runtime::Runtime) -> Self {
+        self.runtime = Some(runtime);
+        self
+    }
+    /// Create the kwui Application.
+    pub fn build(self) -> Result<Application, ApplicationError> {
+        let args = self
+            .args
             .into_iter()
-            .map(|a| CString::new(a).unwrap())
-            .collect::<Vec<_>>();
-        let mut args = args
+            .enumerate()
+            .map(|(index, a)| {
+                CString::new(a).map_err(|_| ApplicationError::InvalidArgument { index })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let resource_root_dir = match &self.resource_root {
+            Some(ResourceRoot::Dir(dir)) => Some(
+                CString::new(dir.as_str()).map_err(|_| ApplicationError::InvalidResourceRootDir)?,
+            ),
+            _ => None,
+        };
+        let mut argv = args
             .iter()
-            .map(|s| s.as_ptr() as *mut std::os::raw::c_char)
+            .map(|s| s.as_ptr() as *mut c_char)
+            .chain(std::iter::once(std::ptr::null_mut()))
             .collect::<Vec<_>>();
         let argc = args.len() as _;
-        let inner = unsafe {
-            #[cfg(debug_assertions)]
-            kwui_Application_enableScriptReload(true);
+        #[cfg(feature = "tokio")]
+        let runtime = match self.runtime {
+            Some(runtime) => runtime,
+            None => crate::runtime::default_runtime()
+                .map_err(|_| ApplicationError::CreateRuntimeFailed)?,
+        };
 
-            kwui_Application_new(argc, args.as_mut_ptr())
+        if let Some(callback) = self.log_callback {
+            crate::logging::set_boxed_callback(callback);
+        }
+        if let Some(hook) = self.panic_hook {
+            crate::panic_guard::set_boxed_hook(hook);
+        }
+        let inner = unsafe {
+            kwui_Application_enableScriptReload(self.script_reload);
+            kwui_Application_new(argc, argv.as_mut_ptr())
+        };
+        if inner.is_null() {
+            return Err(ApplicationError::CreateFailed);
+        }
+        *ALIVE.write().unwrap() = true;
+        let app = Application {
+            inner,
+            _args: args,
+            _argv: argv,
+            #[cfg(feature = "tokio")]
+            runtime: Some(runtime),
         };
+        #[cfg(feature = "tokio")]
+        crate::runtime::install(app.runtime.as_ref().unwrap());
+        match self.resource_root {
+            Some(ResourceRoot::Dir(_)) => unsafe {
+                kwui_Application_setResourceRootDir(app.inner, resource_root_dir.unwrap().as_ptr())
+            },
+            Some(ResourceRoot::Data(data)) => app.set_resource_root_data(data),
+            None => (),
+        }
+        Ok(app)
+    }
+}
+
+impl Default for ApplicationBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        Self { inner }
+impl Application {
+    /// Create the kwui Application.
+    ///
+    /// Arguments are read from `std::env::args()`, and script reload is enabled in debug builds.
+    /// See `ApplicationBuilder` for explicit configuration.
+    pub fn new() -> Self {
+        ApplicationBuilder::new()
+            .args(std::env::args())
+            .script_reload(cfg!(debug_assertions))
+            .build()
+            .expect("create kwui Application failed")
+    }
+    /// Configure the kwui Application explicitly.
+    pub fn builder() -> ApplicationBuilder {
+        ApplicationBuilder::new()
     }
     /// Check running in main thread
     pub fn is_main_thread() -> bool {
@@ -36,13 +246,42 @@ impl Application {
     /// Post a task to run in main thread,
     ///
     /// NOTE: if already on main thread, run the task immediately.
-    pub fn run_in_main_thread<F: FnOnce()>(f: F) {
+    /// The task may be posted from any thread, so it must be `Send + 'static`,
+    /// see `spawn_local` for `!Send` work.
+    ///
+    /// See `run_in_main_thread_with_result` for getting the result of the task.
+    ///
+    /// NOTE: the task is dropped without running if there is no `Application`.
+    pub fn run_in_main_thread<F: FnOnce() + Send + 'static>(f: F) {
         let closure: Box<Closure> = Box::new(Box::new(f) as Closure);
-
+        let udata = Box::into_raw(closure) as *mut c_void;
         unsafe {
-            kwui_Application_runInMainThread(Some(invoke_closure), Box::into_raw(closure) as _)
+            if !post_in_main_thread(invoke_closure, udata) {
+                drop(Box::from_raw(udata as *mut Closure));
+            }
         }
     }
+    /// Spawn a future polled in main thread.
+    ///
+    /// The future doesn't need to be `Send`, it may hold `ScriptValue` or borrow
+    /// thread-local models across `.await`. Wakers can be used from any thread.
+    ///
+    /// ```no_run
+    /// use kwui::{Application, ScriptEngine, ScriptValue};
+    ///
+    /// # async fn load() -> String { String::new() }
+    /// Application::spawn_local(async {
+    ///     let text = load().await;
+    ///     ScriptEngine::post_event1("text-loaded", ScriptValue::from(text));
+    /// });
+    /// ```
+    ///
+    /// NOTE: panics if not called in main thread. kwui runs main thread tasks
+    /// posted from main thread immediately, so a future waking itself on
+    /// every poll keeps the event loop busy.
+    pub fn spawn_local<F: Future<Output = ()> + 'static>(future: F) {
+        crate::executor::spawn_local(future)
+    }
     /// Set resource directory to local folder
     pub fn set_resource_root_dir(&self, dir: &str) {
         let dir = CString::new(dir).unwrap();
@@ -64,14 +303,48 @@ impl Application {
     }
 }
 
+/// Post `callback` to main thread, unless the application is deleted or not created yet.
+///
+/// Holds the flag while posting, so `Application::drop` waits for posts from other threads.
+pub(crate) unsafe fn post_in_main_thread(
+    callback: unsafe extern "C" fn(*mut c_void),
+    udata: *mut c_void,
+) -> bool {
+    let alive = ALIVE.read().unwrap_or_else(|e| e.into_inner());
+    if *alive {
+        kwui_Application_runInMainThread(Some(callback), udata);
+    }
+    *alive
+}
+
 unsafe extern "C" fn invoke_closure(udata: *mut ::std::os::raw::c_void) {
-    eprintln!("invoke_closure called");
-    let closure = std::ptr::read(udata as *mut Closure);
-    closure();
+    let closure = Box::from_raw(udata as *mut Closure);
+    let _ = crate::panic_guard::guard("main thread task", closure);
 }
 
 impl Drop for Application {
     fn drop(&mut self) {
+        // Runtime tasks and timers may post until their shutdown, drop their tasks instead.
+        *ALIVE.write().unwrap_or_else(|e| e.into_inner()) = false;
+        crate::main_thread::shutdown();
+        #[cfg(feature = "tokio")]
+        crate::runtime::shutdown(self.runtime.take());
+        crate::executor::shutdown();
+        crate::timer::shutdown();
         unsafe { kwui_Application_delete(self.inner) }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_rejects_nul_argument() {
+        let err = ApplicationBuilder::new()
+            .args(["app", "bad\0arg"])
+            .build()
+            .err();
+        assert_eq!(err, Some(ApplicationError::InvalidArgument { index: 1 }));
+    }
+}
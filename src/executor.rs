@@ -0,0 +1,118 @@
+//! Single threaded executor, polling futures in main thread.
+//!
+//! Wakers may be used from any thread, they schedule polling with
+//! `kwui_Application_runInMainThread`. Each wakeup polls the future once.
+
+use crate::application::Application;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+type LocalFuture = Pin<Box<dyn Future<Output = ()>>>;
+
+struct Task {
+    /// `None` while being polled.
+    future: Option<LocalFuture>,
+    state: Arc<TaskState>,
+}
+
+struct TaskState {
+    id: usize,
+    scheduled: AtomicBool,
+}
+
+impl Wake for TaskState {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+    fn wake_by_ref(self: &Arc<Self>) {
+        if self.scheduled.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        // Not posted after the application quits, the task is dropped then.
+        unsafe { crate::application::post_in_main_thread(run_task, self.id as _) };
+    }
+}
+
+thread_local! {
+    static TASKS: RefCell<HashMap<usize, Task>> = RefCell::new(HashMap::new());
+    static NEXT_ID: Cell<usize> = const { Cell::new(1) };
+    /// Woken tasks, waiting for the running poll to return.
+    static QUEUE: RefCell<VecDeque<usize>> = const { RefCell::new(VecDeque::new()) };
+    static RUNNING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Spawn `future` in main thread.
+pub(crate) fn spawn_local(future: impl Future<Output = ()> + 'static) {
+    assert!(
+        Application::is_main_thread(),
+        "futures must be spawned in main thread"
+    );
+    let id = NEXT_ID.with(|next| next.replace(next.get() + 1));
+    let state = Arc::new(TaskState {
+        id,
+        scheduled: AtomicBool::new(false),
+    });
+    TASKS.with(|tasks| {
+        tasks.borrow_mut().insert(
+            id,
+            Task {
+                future: Some(Box::pin(future)),
+                state: state.clone(),
+            },
+        )
+    });
+    state.wake();
+}
+
+/// Drop all spawned futures, when the `Application` quits.
+pub(crate) fn shutdown() {
+    // Dropping a future may touch `TASKS`, like spawning or waking.
+    let tasks = TASKS.with(|tasks| std::mem::take(&mut *tasks.borrow_mut()));
+    drop(tasks);
+}
+
+unsafe extern "C" fn run_task(udata: *mut ::std::os::raw::c_void) {
+    // `runInMainThread` runs inline when called in main thread, so this may
+    // be reached from inside a poll. Queue it, the outermost call polls.
+    QUEUE.with(|queue| queue.borrow_mut().push_back(udata as usize));
+    if RUNNING.replace(true) {
+        return;
+    }
+    while let Some(id) = QUEUE.with(|queue| queue.borrow_mut().pop_front()) {
+        if crate::panic_guard::guard("spawned future", || poll_task(id)).is_err() {
+            // The panicked future is dropped.
+            TASKS.with(|tasks| tasks.borrow_mut().remove(&id));
+        }
+    }
+    RUNNING.set(false);
+}
+
+fn poll_task(id: usize) {
+    let task = TASKS.with(|tasks| {
+        let mut tasks = tasks.borrow_mut();
+        let task = tasks.get_mut(&id)?;
+        Some((task.future.take()?, task.state.clone()))
+    });
+    // Finished or dropped.
+    let Some((mut future, state)) = task else {
+        return;
+    };
+    let waker = Waker::from(state.clone());
+    let mut cx = Context::from_waker(&waker);
+    // Waking while being polled queues the next poll.
+    state.scheduled.store(false, Ordering::Release);
+    if let Poll::Ready(()) = future.as_mut().poll(&mut cx) {
+        TASKS.with(|tasks| tasks.borrow_mut().remove(&id));
+        return;
+    }
+    TASKS.with(|tasks| {
+        if let Some(task) = tasks.borrow_mut().get_mut(&id) {
+            task.future = Some(future);
+        }
+    });
+}
@@ -6,10 +6,40 @@
 //! [User Guide](https://wanghoi.github.io/kwui)
 
 #![allow(unused, dead_code)]
+// Lets derive output, which names `::kwui`, compile in the crate's own tests.
+#[cfg(all(test, feature = "derive"))]
+extern crate self as kwui;
+#[doc(hidden)]
+pub mod __private;
 mod application;
+mod executor;
+pub mod logging;
+mod main_thread;
+mod panic_guard;
+#[cfg(feature = "tokio")]
+mod runtime;
 mod script_engine;
+mod script_error;
+mod script_params;
 mod script_value;
+#[cfg(feature = "serde")]
+mod script_serde;
+mod timer;
+mod value;
 
 pub use application::*;
+pub use main_thread::*;
+pub use panic_guard::PanicAction;
+#[cfg(feature = "tokio")]
+pub use runtime::*;
 pub use script_engine::*;
+pub use script_error::*;
+pub use script_params::{LastScriptParam, Rest, ScriptParam};
 pub use script_value::*;
+#[cfg(feature = "serde")]
+pub use script_serde::*;
+pub use timer::*;
+pub use value::*;
+
+#[cfg(feature = "derive")]
+pub use kwui_derive::{FromScriptValue, IntoScriptValue};
@@ -0,0 +1,168 @@
+//! Route log messages of the native runtime into the [`log`](https://docs.rs/log) facade.
+//!
+//! Messages of the C++ runtime and JavaScript `console.*` output are delivered to a single
+//! process wide callback. Calling `init` forwards them as `log::Record`s, so any `log`
+//! subscriber (`env_logger`, `tracing-log`, ...) receives them alongside Rust logs.
+//!
+//! ```no_run
+//! kwui::logging::init();
+//! let app = kwui::Application::new();
+//! ```
+//!
+//! The level and target are parsed from the message when possible, accepted forms are:
+//! - `[level] message`
+//! - `[level][target] message`, `[level] [target] message`
+//! - `level: message`
+//! - `L/target: message`, where `L` is one of `V`, `D`, `I`, `W`, `E`, `F`
+//!
+//! Parsed targets are prefixed by `kwui::`, messages without target use `kwui`.
+//! Messages without a recognizable level are logged at `Info` level.
+
+use kwui_sys::*;
+use log::Level;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::sync::RwLock;
+
+type LogCallback = Box<dyn Fn(&str) + Send + Sync>;
+
+static LOG_CALLBACK: RwLock<Option<LogCallback>> = RwLock::new(None);
+
+const DEFAULT_TARGET: &str = "kwui";
+
+/// Forward native log messages to the `log` facade.
+pub fn init() {
+    set_callback(forward);
+}
+
+/// Receive native log messages with a custom callback, replacing the previous one.
+pub fn set_callback<F: Fn(&str) + Send + Sync + 'static>(callback: F) {
+    set_boxed_callback(Box::new(callback));
+}
+
+pub(crate) fn set_boxed_callback(callback: LogCallback) {
+    *LOG_CALLBACK.write().unwrap() = Some(callback);
+    unsafe { kwui_Application_setLogCallback(Some(invoke_log_callback)) }
+}
+
+/// Parse a native log message, then log it with the `log` facade.
+///
+/// Usable as `ApplicationBuilder::log_callback(kwui::logging::forward)`.
+pub fn forward(msg: &str) {
+    let (level, target, message) = parse(msg);
+    let target = match target {
+        Some(target) => format!("{}::{}", DEFAULT_TARGET, target),
+        None => DEFAULT_TARGET.to_string(),
+    };
+    let logger = log::logger();
+    let metadata = log::Metadata::builder()
+        .level(level)
+        .target(&target)
+        .build();
+    if level > log::max_level() || !logger.enabled(&metadata) {
+        return;
+    }
+    logger.log(
+        &log::Record::builder()
+            .metadata(metadata)
+            .args(format_args!("{}", message))
+            .build(),
+    );
+}
+
+/// Split a native message into level, optional target and message body.
+fn parse(msg: &str) -> (Level, Option<&str>, &str) {
+    let msg = msg.trim_end_matches(['\r', '\n']);
+
+    if let Some(rest) = msg.strip_prefix('[') {
+        if let Some((tag, rest)) = rest.split_once(']') {
+            if let Some(level) = parse_level(tag) {
+                let rest = rest.trim_start();
+                if let Some(inner) = rest.strip_prefix('[') {
+                    if let Some((target, rest)) = inner.split_once(']') {
+                        if is_target(target) {
+                            return (level, Some(target), rest.trim_start());
+                        }
+                    }
+                }
+                return (level, None, rest);
+            }
+        }
+    }
+    if let Some((head, rest)) = msg.split_once(": ") {
+        if let Some((tag, target)) = head.split_once('/') {
+            if tag.len() == 1 && is_target(target) {
+                if let Some(level) = parse_level(tag) {
+                    return (level, Some(target), rest);
+                }
+            }
+        } else if let Some(level) = parse_level(head) {
+            return (level, None, rest);
+        }
+    }
+    (Level::Info, None, msg)
+}
+
+fn parse_level(tag: &str) -> Option<Level> {
+    let level = match tag.trim().to_ascii_lowercase().as_str() {
+        "v" | "verbose" | "trace" => Level::Trace,
+        "d" | "debug" => Level::Debug,
+        "i" | "info" | "log" => Level::Info,
+        "w" | "warn" | "warning" => Level::Warn,
+        "e" | "f" | "error" | "fatal" | "critical" => Level::Error,
+        _ => return None,
+    };
+    Some(level)
+}
+
+fn is_target(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'))
+}
+
+unsafe extern "C" fn invoke_log_callback(msg: *const c_char) {
+    if msg.is_null() {
+        return;
+    }
+    let msg = CStr::from_ptr(msg).to_string_lossy();
+    let _ = crate::panic_guard::guard("log callback", || {
+        if let Some(callback) = LOG_CALLBACK.read().unwrap().as_ref() {
+            callback(&msg);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_level_and_target() {
+        assert_eq!(
+            parse("[warn] low memory\n"),
+            (Level::Warn, None, "low memory")
+        );
+        assert_eq!(
+            parse("[ERROR][script] entry.js:3 syntax error"),
+            (Level::Error, Some("script"), "entry.js:3 syntax error")
+        );
+        assert_eq!(
+            parse("[debug] [console] hello"),
+            (Level::Debug, Some("console"), "hello")
+        );
+        assert_eq!(
+            parse("info: dialog shown"),
+            (Level::Info, None, "dialog shown")
+        );
+        assert_eq!(
+            parse("W/kwui: slow frame"),
+            (Level::Warn, Some("kwui"), "slow frame")
+        );
+        assert_eq!(
+            parse("[not a level] message"),
+            (Level::Info, None, "[not a level] message")
+        );
+        assert_eq!(parse("plain text"), (Level::Info, None, "plain text"));
+    }
+}
@@ -0,0 +1,222 @@
+use crate::application::Application;
+use crate::panic_guard::panic_message;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::task::{Context, Poll, Waker};
+
+/// Error of a task posted by `Application::run_in_main_thread_with_result`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MainThreadError {
+    /// The task panicked, with the panic message.
+    Panicked(String),
+    /// The task was dropped without running, the application has quit.
+    Cancelled,
+    /// Blocking on the result in main thread, which would never finish.
+    Deadlock,
+}
+
+impl std::fmt::Display for MainThreadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MainThreadError::Panicked(msg) => write!(f, "main thread task panicked: {}", msg),
+            MainThreadError::Cancelled => f.write_str("main thread task cancelled"),
+            MainThreadError::Deadlock => {
+                f.write_str("waiting for main thread task in main thread would deadlock")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MainThreadError {}
+
+struct Slot<R> {
+    result: Option<Result<R, MainThreadError>>,
+    waker: Option<Waker>,
+}
+
+struct Shared<R> {
+    slot: Mutex<Slot<R>>,
+    cond: Condvar,
+}
+
+impl<R> Shared<R> {
+    fn complete(&self, result: Result<R, MainThreadError>) {
+        let mut slot = self.slot.lock().unwrap();
+        if slot.result.is_some() {
+            return;
+        }
+        slot.result = Some(result);
+        let waker = slot.waker.take();
+        drop(slot);
+        self.cond.notify_all();
+        // Waking may poll the awaiting future immediately, don't hold the lock.
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+trait Cancel: Send + Sync {
+    fn cancel(&self);
+}
+
+impl<R: Send> Cancel for Shared<R> {
+    fn cancel(&self) {
+        self.complete(Err(MainThreadError::Cancelled));
+    }
+}
+
+/// Results of tasks not run yet, cancelled by `shutdown`.
+static PENDING: Mutex<BTreeMap<u64, Weak<dyn Cancel>>> = Mutex::new(BTreeMap::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Completes the result with `Cancelled` if the task is dropped without running.
+struct Completer<R: Send> {
+    id: u64,
+    shared: Arc<Shared<R>>,
+}
+
+impl<R: Send + 'static> Completer<R> {
+    fn new() -> (Completer<R>, MainThreadResult<R>) {
+        let shared = Arc::new(Shared {
+            slot: Mutex::new(Slot {
+                result: None,
+                waker: None,
+            }),
+            cond: Condvar::new(),
+        });
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let weak = Arc::downgrade(&shared) as Weak<dyn Cancel>;
+        PENDING.lock().unwrap().insert(id, weak);
+        let result = MainThreadResult {
+            shared: shared.clone(),
+        };
+        (Completer { id, shared }, result)
+    }
+}
+
+impl<R: Send> Drop for Completer<R> {
+    fn drop(&mut self) {
+        PENDING.lock().unwrap().remove(&self.id);
+        self.shared.complete(Err(MainThreadError::Cancelled));
+    }
+}
+
+/// Fail results of tasks not run yet with `Cancelled`, when the `Application` quits.
+///
+/// The native side drops posted tasks without running them.
+pub(crate) fn shutdown() {
+    let pending = std::mem::take(&mut *PENDING.lock().unwrap());
+    for shared in pending.into_values().filter_map(|weak| weak.upgrade()) {
+        shared.cancel();
+    }
+}
+
+/// Result of a task running in main thread.
+///
+/// Block on it with `wait`, or `.await` it.
+pub struct MainThreadResult<R> {
+    shared: Arc<Shared<R>>,
+}
+
+impl<R> MainThreadResult<R> {
+    /// Check the task has finished, `wait` won't block then.
+    pub fn is_finished(&self) -> bool {
+        self.shared.slot.lock().unwrap().result.is_some()
+    }
+    /// Block current thread until the task finishes.
+    ///
+    /// Returns `MainThreadError::Deadlock` instead of blocking forever,
+    /// if called in main thread before the task finishes.
+    pub fn wait(self) -> Result<R, MainThreadError> {
+        let mut slot = self.shared.slot.lock().unwrap();
+        loop {
+            if let Some(result) = slot.result.take() {
+                return result;
+            }
+            if Application::is_main_thread() {
+                return Err(MainThreadError::Deadlock);
+            }
+            slot = self.shared.cond.wait(slot).unwrap();
+        }
+    }
+}
+
+impl<R> Future for MainThreadResult<R> {
+    type Output = Result<R, MainThreadError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = self.shared.slot.lock().unwrap();
+        match slot.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<R> std::fmt::Debug for MainThreadResult<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MainThreadResult")
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+
+impl Application {
+    /// Post a task to run in main thread, and get its result.
+    ///
+    /// NOTE: if already on main thread, run the task immediately.
+    ///
+    /// ```no_run
+    /// use kwui::{Application, ScriptEngine};
+    ///
+    /// std::thread::spawn(|| {
+    ///     let title = Application::run_in_main_thread_with_result(|| {
+    ///         ScriptEngine::call_global_function("getTitle", &[]).to_string()
+    ///     })
+    ///     .wait()
+    ///     .unwrap();
+    /// });
+    /// ```
+    pub fn run_in_main_thread_with_result<F, R>(f: F) -> MainThreadResult<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let (completer, result) = Completer::new();
+        Application::run_in_main_thread(move || {
+            let result = catch_unwind(AssertUnwindSafe(f))
+                .map_err(|payload| MainThreadError::Panicked(panic_message(&*payload)));
+            completer.shared.complete(result);
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dropped_task_is_cancelled() {
+        let (completer, result) = Completer::<i32>::new();
+        drop(completer);
+        assert!(result.is_finished());
+        assert_eq!(result.wait(), Err(MainThreadError::Cancelled));
+    }
+
+    #[test]
+    fn shutdown_cancels_pending_task() {
+        let (completer, result) = Completer::<i32>::new();
+        shutdown();
+        assert_eq!(result.wait(), Err(MainThreadError::Cancelled));
+        drop(completer);
+    }
+}
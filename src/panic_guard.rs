@@ -0,0 +1,69 @@
+//! Keep panics of Rust callbacks from unwinding into the native side.
+
+use std::any::Any;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::RwLock;
+
+/// What to do after a Rust callback called by kwui panicked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PanicAction {
+    /// Report the panic and keep running, exported functions return a JavaScript `Error` object.
+    #[default]
+    Continue,
+    /// Abort the process.
+    Abort,
+}
+
+type PanicHook = Box<dyn Fn(&str) -> PanicAction + Send + Sync>;
+
+static PANIC_HOOK: RwLock<Option<PanicHook>> = RwLock::new(None);
+
+pub(crate) fn set_boxed_hook(hook: PanicHook) {
+    *PANIC_HOOK.write().unwrap() = Some(hook);
+}
+
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Run callback `f` called from native side, returns the panic message if it panicked.
+///
+/// The panic hook decides whether to abort, it's only logged by default.
+pub(crate) fn guard<R>(what: &str, f: impl FnOnce() -> R) -> Result<R, String> {
+    catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        let msg = panic_message(&*payload);
+        // A panicking hook can't be trusted either.
+        let action = catch_unwind(AssertUnwindSafe(|| match PANIC_HOOK.read() {
+            Ok(hook) => hook
+                .as_ref()
+                .map_or(PanicAction::Continue, |hook| hook(&msg)),
+            Err(_) => PanicAction::Abort,
+        }))
+        .unwrap_or(PanicAction::Abort);
+        log::error!("{} panicked: {}", what, msg);
+        if action == PanicAction::Abort {
+            std::process::abort();
+        }
+        msg
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guard_catches_panic() {
+        assert_eq!(guard("test", || 1), Ok(1));
+        assert_eq!(
+            guard("test", || -> i32 { panic!("bad {}", 1) }),
+            Err("bad 1".to_string())
+        );
+    }
+}
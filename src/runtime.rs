@@ -0,0 +1,99 @@
+//! tokio integration, enabled by the `tokio` feature.
+
+use crate::application::Application;
+use std::future::Future;
+use std::sync::Mutex;
+use tokio::runtime::{Handle, Runtime};
+use tokio::task::JoinHandle;
+
+/// Handle of the runtime owned by current `Application`.
+static HANDLE: Mutex<Option<Handle>> = Mutex::new(None);
+
+pub(crate) fn default_runtime() -> std::io::Result<Runtime> {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+}
+
+pub(crate) fn install(rt: &Runtime) {
+    *HANDLE.lock().unwrap() = Some(rt.handle().clone());
+}
+
+/// Stop the runtime, before the native application is deleted.
+///
+/// Pending main thread results must be cancelled first. Tasks still blocking a
+/// worker are left running in the background, instead of blocking main thread,
+/// their main thread tasks are dropped without running.
+pub(crate) fn shutdown(rt: Option<Runtime>) {
+    HANDLE.lock().unwrap().take();
+    if let Some(rt) = rt {
+        rt.shutdown_background();
+    }
+}
+
+impl Application {
+    /// Spawn a future onto the tokio runtime owned by the `Application`.
+    ///
+    /// Use `MainThreadHandle` to get back to main thread when the work is done.
+    ///
+    /// NOTE: panics if there is no `Application`.
+    pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let handle = HANDLE
+            .lock()
+            .unwrap()
+            .clone()
+            .expect("no tokio runtime, create Application first");
+        handle.spawn(future)
+    }
+    /// Get a handle to run tasks in main thread, from any thread.
+    pub fn main_thread_handle() -> MainThreadHandle {
+        MainThreadHandle { _private: () }
+    }
+}
+
+/// Run tasks in main thread, from runtime worker threads.
+///
+/// ```no_run
+/// use kwui::{Application, ScriptEngine};
+///
+/// let main_thread = Application::main_thread_handle();
+/// Application::spawn(async move {
+///     let text = String::from("loaded");
+///     let len = main_thread
+///         .run_async(move || {
+///             ScriptEngine::post_event1("text-loaded", text.as_str());
+///             text.len()
+///         })
+///         .await;
+///     assert_eq!(len, 6);
+/// });
+/// ```
+#[derive(Debug, Clone)]
+pub struct MainThreadHandle {
+    _private: (),
+}
+
+impl MainThreadHandle {
+    /// Post `f` to run in main thread.
+    ///
+    /// NOTE: if already on main thread, run `f` immediately.
+    pub fn run<F: FnOnce() + Send + 'static>(&self, f: F) {
+        Application::run_in_main_thread(f)
+    }
+    /// Run `f` in main thread, and wait for its result.
+    ///
+    /// NOTE: the future panics if `f` panics, or the application quits before `f` runs.
+    /// Use `Application::run_in_main_thread_with_result` to handle these errors.
+    pub fn run_async<F, R>(&self, f: F) -> impl Future<Output = R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let result = Application::run_in_main_thread_with_result(f);
+        async move { result.await.unwrap_or_else(|e| panic!("{}", e)) }
+    }
+}
@@ -1,7 +1,13 @@
 use kwui_sys::*;
 use log;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::{ffi::CString, io::Read};
 
+use crate::script_error::ScriptError;
+use crate::script_params::{RawArgs, ScriptFuntionParams};
 use crate::script_value::{FromScriptValue, IntoScriptValue, ScriptValue};
 
 /// The global script engine
@@ -18,6 +24,62 @@ pub struct ScriptEventHandler {
 impl Drop for ScriptEventHandler {
     fn drop(&mut self) {
         ScriptEngine::remove_event_listener(self);
+        // A running call keeps its own reference, see `invoke_closure`.
+        unsafe { drop(Rc::from_raw(self.inner as *const Callback)) }
+    }
+}
+
+/// Handler retrieved from `add_global_function`, the function is removed when it's dropped.
+///
+/// Call `detach` to keep the function exported until the application quits,
+/// like `add_global_function` did before returning a handle.
+#[must_use = "the function is removed when the handle is dropped, call `detach()` to keep it"]
+pub struct GlobalFunctionHandle {
+    name: CString,
+    inner: *mut std::os::raw::c_void,
+}
+
+thread_local! {
+    /// Closure currently exported by each global function name.
+    static GLOBAL_FUNCTIONS: RefCell<HashMap<CString, usize>> = RefCell::new(HashMap::new());
+}
+
+impl GlobalFunctionHandle {
+    fn register(name: &str, closure: Box<Callback>) -> Self {
+        let name = CString::new(name).unwrap();
+        let inner = Rc::into_raw(Rc::<Callback>::from(closure)) as *mut std::os::raw::c_void;
+        GLOBAL_FUNCTIONS.with_borrow_mut(|functions| functions.insert(name.clone(), inner as usize));
+        unsafe {
+            kwui_ScriptEngine_addGlobalFunction(name.as_ptr(), Some(invoke_closure), inner);
+        }
+        Self { name, inner }
+    }
+    /// Keep the function exported until the application quits.
+    pub fn detach(self) {
+        std::mem::forget(self)
+    }
+}
+
+impl Drop for GlobalFunctionHandle {
+    fn drop(&mut self) {
+        // The name may be exported again by another function.
+        // `GLOBAL_FUNCTIONS` is gone if the handle is dropped at thread exit.
+        let current = GLOBAL_FUNCTIONS
+            .try_with(|functions| {
+                let mut functions = functions.borrow_mut();
+                if functions.get(&self.name) == Some(&(self.inner as usize)) {
+                    functions.remove(&self.name);
+                    true
+                } else {
+                    false
+                }
+            })
+            .unwrap_or(false);
+        if current {
+            unsafe { kwui_ScriptEngine_removeGlobalFunction(self.name.as_ptr()) }
+        }
+        // A running call keeps its own reference, see `invoke_closure`.
+        unsafe { drop(Rc::from_raw(self.inner as *const Callback)) }
     }
 }
 
@@ -43,26 +105,74 @@ impl ScriptEngine {
         };
         ScriptValue::from_inner(inner)
     }
-    /// Export Rust function to JavaScript
-    pub fn add_global_function<R, Fun, Args>(name: &str, func: Fun)
+    /// Export Rust function to JavaScript, until the returned handle is dropped.
+    ///
+    /// A `ScriptError` from the function is logged, and returned to JavaScript as `{ name, message }`,
+    /// `name` is `Error`, `TypeError` or `RangeError`.
+    ///
+    /// NOTE: the kwui C API can't throw from native functions, script should check the result.
+    pub fn add_global_function<R, Fun, Args>(name: &str, func: Fun) -> GlobalFunctionHandle
     where
         Fun: ScriptFunction<R, Args> + 'static,
     {
-        let name = CString::new(name).unwrap();
-        let closure: Box<Callback> = Box::new(Box::new(move |params| -> Result<ScriptValue, ()> {
-            func.invoke(params)
+        let fn_name = name.to_string();
+        let closure: Box<Callback> = Box::new(Box::new(move |params| -> Result<ScriptValue, ScriptError> {
+            let ret = if strict_arguments() {
+                func.invoke_strict(params)
+            } else {
+                func.invoke(params)
+            };
+            ret.map_err(|e| e.in_function(&fn_name))
         }) as Callback);
-        unsafe {
-            kwui_ScriptEngine_addGlobalFunction(
-                name.as_ptr(),
-                Some(invoke_closure),
-                Box::into_raw(closure) as _,
-            );
-        }
+        GlobalFunctionHandle::register(name, closure)
+    }
+    /// Export Rust `FnMut` closure to JavaScript, until the returned handle is dropped.
+    ///
+    /// The closure can own its state, instead of a `thread_local!` model.
+    /// Calling it again while it's running, from script it calls, returns an `Error` object.
+    ///
+    /// ```no_run
+    /// use kwui::ScriptEngine;
+    ///
+    /// let mut count = 0;
+    /// let _handle = ScriptEngine::add_global_function_mut("nextId", move || {
+    ///     count += 1;
+    ///     count
+    /// });
+    /// ```
+    pub fn add_global_function_mut<R, Fun, Args>(name: &str, func: Fun) -> GlobalFunctionHandle
+    where
+        Fun: ScriptFunctionMut<R, Args> + 'static,
+    {
+        let func = RefCell::new(func);
+        let fn_name = name.to_string();
+        let closure: Box<Callback> = Box::new(Box::new(
+            move |params: &[ScriptValue]| -> Result<ScriptValue, ScriptError> {
+                let mut func = func.try_borrow_mut().map_err(|_| {
+                    ScriptError::custom(format!("function `{}` called recursively", fn_name))
+                })?;
+                let ret = if strict_arguments() {
+                    func.invoke_mut_strict(params)
+                } else {
+                    func.invoke_mut(params)
+                };
+                ret.map_err(|e| e.in_function(&fn_name))
+            },
+        ) as Callback);
+        GlobalFunctionHandle::register(name, closure)
+    }
+    /// Check arguments of all exported functions strictly, like wrapping them in `Strict`.
+    ///
+    /// Disabled by default, missing or mistyped arguments are `Default::default()` then.
+    pub fn set_strict_arguments(enable: bool) {
+        STRICT_ARGUMENTS.store(enable, Ordering::Relaxed);
     }
     /// Undo export of Rust function
+    ///
+    /// The closure is freed when its `GlobalFunctionHandle` is dropped.
     pub fn remove_global_function(name: &str) {
         let name = CString::new(name).unwrap();
+        GLOBAL_FUNCTIONS.with_borrow_mut(|functions| functions.remove(&name));
         unsafe {
             kwui_ScriptEngine_removeGlobalFunction(name.as_ptr());
         }
@@ -76,11 +186,11 @@ impl ScriptEngine {
         let c_event = CString::new(event).unwrap();
         let e = event.to_string();
         let closure: Box<Callback> = Box::new(Box::new(
-            move |args: &[ScriptValue]| -> Result<ScriptValue, ()> {
+            move |args: &[ScriptValue]| -> Result<ScriptValue, ScriptError> {
                 func.invoke(args)
             },
         ) as Callback);
-        let inner = Box::into_raw(closure) as _;
+        let inner = Rc::into_raw(Rc::<Callback>::from(closure)) as _;
         unsafe {
             kwui_ScriptEngine_addEventListener(c_event.as_ptr(), Some(invoke_closure), inner);
         }
@@ -107,30 +217,67 @@ impl ScriptEngine {
             kwui_ScriptEngine_postEvent0(c_event.as_ptr());
         }
     }
+    /// Trigger an event and run the listeners now, returns their result.
+    ///
+    /// Unlike `post_event0`, listeners are called before returning.
+    ///
+    /// NOTE: the result is the value kwui returns for the event, as is, `null` if there is none.
+    pub fn send_event0(event: &str) -> ScriptValue {
+        let c_event = CString::new(event).unwrap();
+        unsafe { event_result(kwui_ScriptEngine_sendEvent0(c_event.as_ptr())) }
+    }
+    /// Trigger an event with data and run the listeners now, returns their result.
+    ///
+    /// ```no_run
+    /// use kwui::ScriptEngine;
+    ///
+    /// let vetoed = ScriptEngine::send_event1("dialog:request-close", "main");
+    /// if !vetoed.to_bool() {
+    ///     // close the dialog
+    /// }
+    /// ```
+    pub fn send_event1(event: &str, data: impl IntoScriptValue) -> ScriptValue {
+        let c_event = CString::new(event).unwrap();
+        let data = match data.into_script_value() {
+            Ok(data) => data,
+            Err(e) => {
+                log::warn!("ScriptEngine::send_event '{}' failed: {}", event, e);
+                return ScriptValue::new_null();
+            }
+        };
+        unsafe { event_result(kwui_ScriptEngine_sendEvent1(c_event.as_ptr(), data.inner())) }
+    }
     /// Trigger an event with data, both JavaScript and Rust event listeners will be notified.
     pub fn post_event1(event: &str, data: impl IntoScriptValue) {
         let c_event = CString::new(event).unwrap();
-        let data = data.into_script_value();
-        if let Ok(data) = data {
-            unsafe {
+        match data.into_script_value() {
+            Ok(data) => unsafe {
                 kwui_ScriptEngine_postEvent1(c_event.as_ptr(), data.inner());
-            }
-        } else {
-            log::warn!("ScriptEngine::post_event '{}' failed", event);
+            },
+            Err(e) => log::warn!("ScriptEngine::post_event '{}' failed: {}", event, e),
         }
     }
 }
 
+/// Take the result of `sendEvent0` and `sendEvent1`, which may be a null pointer.
+unsafe fn event_result(inner: *mut kwui_ScriptValue) -> ScriptValue {
+    if inner.is_null() {
+        ScriptValue::new_null()
+    } else {
+        ScriptValue::from_inner(inner)
+    }
+}
+
 /// Make script function args, return `[ScriptValue]`
 #[macro_export]
 macro_rules! make_args {
-	() => { { let args : [$crate::value::Value; 0] = []; args } };
+	() => { { let args : [$crate::ScriptValue; 0] = []; args } };
 
 	( $($s:expr),* ) => {
 		{
 			let args = [
 			$(
-				$crate::script_value::ScriptValue::from($s)
+				$crate::ScriptValue::from($s)
 			 ),*
 			];
 			args
@@ -138,55 +285,13 @@ macro_rules! make_args {
 	};
 }
 
-type Callback<'a> = Box<dyn Fn(&'a [ScriptValue]) -> Result<ScriptValue, ()> + 'a>;
-
-pub(crate) trait ScriptFuntionParams: Sized {
-    fn from_params(params: &[ScriptValue]) -> Result<Self, ()>;
-}
-
-macro_rules! impl_script_function_param {
-    () => {
-        impl_script_function_param!(@expand 0usize ; ;);
-    };
-    ($($p:ident),*) => {
-        impl_script_function_param!(@expand 0usize ; ; $($p)*);
-    };
-    (@expand $n:expr ; $([$idx:expr, $pa:ident]),* ; $a:ident $($p:ident)*) => {
-        impl_script_function_param!(@expand $n + 1usize; $([$idx, $pa],)* [$n, $a] ; $($p)*);
-    };
-    (@expand $n:expr ; $([$idx:expr, $pa:tt]),* ;) => {
-        impl<$($pa,)*> ScriptFuntionParams for ($($pa,)*)
-        where
-        $(
-            $pa: FromScriptValue + Default,
-        )*
-        {
-            fn from_params(param: &[ScriptValue]) -> Result<Self, ()> {
-                Ok((
-                    $(
-                        param
-                            .get($idx)
-                            .and_then(|arg| $pa::from_script_value(arg).ok())
-                            .unwrap_or_default(),
-                    )*
-                ))
-            }
-        }
-    };
-}
-impl_script_function_param!();
-impl_script_function_param!(A1);
-impl_script_function_param!(A1, A2);
-impl_script_function_param!(A1, A2, A3);
-impl_script_function_param!(A1, A2, A3, A4);
-impl_script_function_param!(A1, A2, A3, A4, A5);
-impl_script_function_param!(A1, A2, A3, A4, A5, A6);
-impl_script_function_param!(A1, A2, A3, A4, A5, A6, A7);
-impl_script_function_param!(A1, A2, A3, A4, A5, A6, A7, A8);
+type Callback<'a> = Box<dyn Fn(&'a [ScriptValue]) -> Result<ScriptValue, ScriptError> + 'a>;
 
 /// An invokable function with specified signature.
 pub trait ScriptFunction<R, Args> {
-    fn invoke(&self, params: &[ScriptValue]) -> Result<ScriptValue, ()>;
+    fn invoke(&self, params: &[ScriptValue]) -> Result<ScriptValue, ScriptError>;
+    #[doc(hidden)]
+    fn invoke_strict(&self, params: &[ScriptValue]) -> Result<ScriptValue, ScriptError>;
 }
 macro_rules! impl_script_function {
     ($($arg:ident),*) => {
@@ -197,8 +302,12 @@ macro_rules! impl_script_function {
             ($($arg,)*): ScriptFuntionParams,
             Fun: Fn($($arg),*) -> R,
         {
-            fn invoke(&self, params: &[ScriptValue]) -> Result<ScriptValue, ()> {
-                let ($($arg,)*) = ScriptFuntionParams::from_params(params)?;
+            fn invoke(&self, params: &[ScriptValue]) -> Result<ScriptValue, ScriptError> {
+                let ($($arg,)*) = ScriptFuntionParams::from_params(params, false)?;
+                self($($arg,)*).into_script_value()
+            }
+            fn invoke_strict(&self, params: &[ScriptValue]) -> Result<ScriptValue, ScriptError> {
+                let ($($arg,)*) = ScriptFuntionParams::from_params(params, true)?;
                 self($($arg,)*).into_script_value()
             }
         }
@@ -213,6 +322,127 @@ impl_script_function!(A1, A2, A3, A4, A5);
 impl_script_function!(A1, A2, A3, A4, A5, A6);
 impl_script_function!(A1, A2, A3, A4, A5, A6, A7);
 impl_script_function!(A1, A2, A3, A4, A5, A6, A7, A8);
+impl_script_function!(A1, A2, A3, A4, A5, A6, A7, A8, A9);
+impl_script_function!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10);
+impl_script_function!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11);
+impl_script_function!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12);
+impl_script_function!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13);
+impl_script_function!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13, A14);
+impl_script_function!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13, A14, A15);
+impl_script_function!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13, A14, A15, A16);
+
+/// Raw handler taking all arguments.
+impl<Fun, R> ScriptFunction<R, RawArgs> for Fun
+where
+    R: IntoScriptValue,
+    Fun: Fn(&[ScriptValue]) -> R,
+{
+    fn invoke(&self, params: &[ScriptValue]) -> Result<ScriptValue, ScriptError> {
+        self(params).into_script_value()
+    }
+    fn invoke_strict(&self, params: &[ScriptValue]) -> Result<ScriptValue, ScriptError> {
+        self(params).into_script_value()
+    }
+}
+
+/// An invokable `FnMut` function with specified signature.
+pub trait ScriptFunctionMut<R, Args> {
+    fn invoke_mut(&mut self, params: &[ScriptValue]) -> Result<ScriptValue, ScriptError>;
+    #[doc(hidden)]
+    fn invoke_mut_strict(&mut self, params: &[ScriptValue]) -> Result<ScriptValue, ScriptError>;
+}
+macro_rules! impl_script_function_mut {
+    ($($arg:ident),*) => {
+        #[allow(non_snake_case)]
+        impl<Fun, R, $($arg,)*> ScriptFunctionMut<R, ($($arg,)*)> for Fun
+        where
+            R: IntoScriptValue,
+            ($($arg,)*): ScriptFuntionParams,
+            Fun: FnMut($($arg),*) -> R,
+        {
+            fn invoke_mut(&mut self, params: &[ScriptValue]) -> Result<ScriptValue, ScriptError> {
+                let ($($arg,)*) = ScriptFuntionParams::from_params(params, false)?;
+                self($($arg,)*).into_script_value()
+            }
+            fn invoke_mut_strict(
+                &mut self,
+                params: &[ScriptValue],
+            ) -> Result<ScriptValue, ScriptError> {
+                let ($($arg,)*) = ScriptFuntionParams::from_params(params, true)?;
+                self($($arg,)*).into_script_value()
+            }
+        }
+    };
+}
+impl_script_function_mut!();
+impl_script_function_mut!(A1);
+impl_script_function_mut!(A1, A2);
+impl_script_function_mut!(A1, A2, A3);
+impl_script_function_mut!(A1, A2, A3, A4);
+impl_script_function_mut!(A1, A2, A3, A4, A5);
+impl_script_function_mut!(A1, A2, A3, A4, A5, A6);
+impl_script_function_mut!(A1, A2, A3, A4, A5, A6, A7);
+impl_script_function_mut!(A1, A2, A3, A4, A5, A6, A7, A8);
+impl_script_function_mut!(A1, A2, A3, A4, A5, A6, A7, A8, A9);
+impl_script_function_mut!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10);
+impl_script_function_mut!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11);
+impl_script_function_mut!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12);
+impl_script_function_mut!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13);
+impl_script_function_mut!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13, A14);
+impl_script_function_mut!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13, A14, A15);
+impl_script_function_mut!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13, A14, A15, A16);
+
+/// Raw handler taking all arguments.
+impl<Fun, R> ScriptFunctionMut<R, RawArgs> for Fun
+where
+    R: IntoScriptValue,
+    Fun: FnMut(&[ScriptValue]) -> R,
+{
+    fn invoke_mut(&mut self, params: &[ScriptValue]) -> Result<ScriptValue, ScriptError> {
+        self(params).into_script_value()
+    }
+    fn invoke_mut_strict(&mut self, params: &[ScriptValue]) -> Result<ScriptValue, ScriptError> {
+        self(params).into_script_value()
+    }
+}
+
+/// Check arguments of an exported function strictly, regardless of `ScriptEngine::set_strict_arguments`.
+///
+/// Missing, extra or mistyped arguments return a `TypeError` object naming the function and
+/// argument, instead of becoming `Default::default()`. Only `Option` parameters may be omitted.
+///
+/// ```no_run
+/// use kwui::{ScriptEngine, Strict};
+///
+/// fn set_title(title: String, subtitle: Option<String>) {}
+/// ScriptEngine::add_global_function("setTitle", Strict(set_title)).detach();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Strict<F>(pub F);
+
+impl<R, Args, F: ScriptFunction<R, Args>> ScriptFunction<R, Args> for Strict<F> {
+    fn invoke(&self, params: &[ScriptValue]) -> Result<ScriptValue, ScriptError> {
+        self.0.invoke_strict(params)
+    }
+    fn invoke_strict(&self, params: &[ScriptValue]) -> Result<ScriptValue, ScriptError> {
+        self.0.invoke_strict(params)
+    }
+}
+
+impl<R, Args, F: ScriptFunctionMut<R, Args>> ScriptFunctionMut<R, Args> for Strict<F> {
+    fn invoke_mut(&mut self, params: &[ScriptValue]) -> Result<ScriptValue, ScriptError> {
+        self.0.invoke_mut_strict(params)
+    }
+    fn invoke_mut_strict(&mut self, params: &[ScriptValue]) -> Result<ScriptValue, ScriptError> {
+        self.0.invoke_mut_strict(params)
+    }
+}
+
+static STRICT_ARGUMENTS: AtomicBool = AtomicBool::new(false);
+
+fn strict_arguments() -> bool {
+    STRICT_ARGUMENTS.load(Ordering::Relaxed)
+}
 
 unsafe extern "C" fn invoke_closure(
     argc: ::std::os::raw::c_int,
@@ -220,7 +450,6 @@ unsafe extern "C" fn invoke_closure(
     udata: *mut ::std::os::raw::c_void,
 ) -> *mut kwui_ScriptValue {
     // eprintln!("invoke_closure called argc={} argv={:?}", argc, argv);
-    let closure = (udata as *mut Callback).as_mut().unwrap();
     let args: Vec<ScriptValue> = if argc > 0 {
         std::slice::from_raw_parts(argv, argc as _)
             .iter()
@@ -229,20 +458,29 @@ unsafe extern "C" fn invoke_closure(
     } else {
         Vec::new()
     };
-    let ret = closure(&args);
+    // The handle may be dropped by the closure itself, keep it alive until it returns.
+    let closure = udata as *const Callback;
+    Rc::increment_strong_count(closure);
+    let closure = Rc::from_raw(closure);
+    let ret = crate::panic_guard::guard("native function", || (*closure)(&args));
+    drop(closure);
     for a in args.into_iter() {
         a.leak();
     }
     match ret {
-        Ok(ret) => ret.leak(),
-        Err(_) => kwui_ScriptValue_newNull(),
+        Ok(Ok(ret)) => ret.leak(),
+        Ok(Err(e)) => {
+            log::error!("{}", e);
+            e.to_js_error().leak()
+        }
+        Err(msg) => ScriptValue::new_error("Error", &format!("Rust panic: {}", msg)).leak(),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Application;
+    use crate::{Application, Rest};
 
     fn f0() {
         eprintln!("f0 called");
@@ -259,14 +497,93 @@ mod tests {
     #[test]
     fn global_func() {
         let app = Application::new();
-        ScriptEngine::add_global_function("f0", f0);
+        let _f0 = ScriptEngine::add_global_function("f0", f0);
         ScriptEngine::call_global_function("f0", &[]);
 
-        ScriptEngine::add_global_function("f1", f1);
+        let _f1 = ScriptEngine::add_global_function("f1", f1);
         ScriptEngine::call_global_function("f1", &make_args!("a", 2));
 
         let a = on_test_event as *const ();
         let _handler = ScriptEngine::add_event_listener("test-event", on_test_event);
         ScriptEngine::post_event1("test-event", 1.23);
     }
+
+    #[test]
+    fn send_event_result() {
+        let app = Application::new();
+        let _h = ScriptEngine::add_event_listener("test-veto", |_: String| true);
+        assert!(ScriptEngine::send_event1("test-veto", "main").to_bool());
+        assert!(ScriptEngine::send_event0("test-none").is_null());
+    }
+
+    #[test]
+    fn result_return() {
+        let app = Application::new();
+        let parse = |s: String| s.parse::<i32>().map_err(ScriptError::custom);
+        let _parse = ScriptEngine::add_global_function("parse", parse);
+        assert_eq!(ScriptEngine::call_global_function("parse", &make_args!("7")).to_int(), 7);
+        let err = ScriptEngine::call_global_function("parse", &make_args!("x"));
+        assert_eq!(err.get_by_str::<String>("name"), "Error");
+    }
+
+    #[test]
+    fn strict_argument_errors() {
+        let app = Application::new();
+        let _f1 = ScriptEngine::add_global_function("f1", Strict(f1));
+        let err = ScriptEngine::call_global_function("f1", &make_args!("a"));
+        assert_eq!(err.get_by_str::<String>("name"), "TypeError");
+        assert_eq!(
+            err.get_by_str::<String>("message"),
+            "`f1` argument 2: type mismatch, expected f32, found undefined"
+        );
+
+        let add = |a: i32, b: Option<i32>| a + b.unwrap_or(1);
+        let _add = ScriptEngine::add_global_function("add", Strict(add));
+        assert_eq!(ScriptEngine::call_global_function("add", &make_args!(1)).to_int(), 2);
+        let err = ScriptEngine::call_global_function("add", &make_args!(1, 2, 3));
+        assert_eq!(err.get_by_str::<String>("name"), "TypeError");
+    }
+
+    #[test]
+    fn global_function_handle() {
+        let app = Application::new();
+        let mut count = 0;
+        let handle = ScriptEngine::add_global_function_mut("next", move || {
+            count += 1;
+            count
+        });
+        ScriptEngine::call_global_function("next", &make_args!());
+        assert_eq!(ScriptEngine::call_global_function("next", &make_args!()).to_int(), 2);
+        drop(handle);
+        assert!(!ScriptEngine::call_global_function("next", &make_args!()).is_number());
+    }
+
+    #[test]
+    fn drop_handle_while_called() {
+        thread_local! {
+            static ONCE: RefCell<Option<GlobalFunctionHandle>> = const { RefCell::new(None) };
+        }
+        let app = Application::new();
+        let once = |v: i32| {
+            ONCE.take();
+            v
+        };
+        ONCE.set(Some(ScriptEngine::add_global_function("once", once)));
+        assert_eq!(ScriptEngine::call_global_function("once", &make_args!(1)).to_int(), 1);
+        assert!(!ScriptEngine::call_global_function("once", &make_args!(2)).is_number());
+    }
+
+    #[test]
+    fn rest_and_raw_arguments() {
+        let app = Application::new();
+        let join = |sep: String, Rest(parts): Rest<Vec<String>>| parts.join(&sep);
+        let _join = ScriptEngine::add_global_function("join", join);
+        let ret = ScriptEngine::call_global_function("join", &make_args!("-", "a", "b", "c"));
+        assert_eq!(ret.to_string(), "a-b-c");
+
+        let count = |args: &[ScriptValue]| args.len() as i32;
+        let _count = ScriptEngine::add_global_function("count", count);
+        let ret = ScriptEngine::call_global_function("count", &make_args!(1, "a", ()));
+        assert_eq!(ret.to_int(), 3);
+    }
 }
@@ -0,0 +1,196 @@
+use crate::script_value::ScriptValue;
+
+/// Error converting between Rust and JavaScript values.
+///
+/// `path` locates the failed value inside the converted one, like `items[3].title`,
+/// it's empty for the converted value itself.
+///
+/// When an exported function fails, or panics, JavaScript gets an error object
+/// `{ name, message }` as the return value, it's not thrown. Callers need to
+/// check the result, like `if (ret && ret.name === "TypeError")`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptError {
+    /// The JavaScript value has unexpected type.
+    TypeMismatch {
+        path: String,
+        expected: &'static str,
+        actual: &'static str,
+    },
+    /// A required object property is missing, or `null`.
+    MissingProperty { path: String },
+    /// The number is not an integer, or `NaN`.
+    NonIntegral { path: String, value: f64 },
+    /// The number can't be represented by the target type.
+    OutOfRange {
+        path: String,
+        value: f64,
+        target: &'static str,
+    },
+    /// The string is not valid UTF-8.
+    InvalidUtf8 { path: String },
+    /// Other errors.
+    Custom { path: String, message: String },
+    /// Argument `index` (from 0) of exported function is missing or invalid, in strict mode.
+    InvalidArgument {
+        function: String,
+        index: usize,
+        error: Box<ScriptError>,
+    },
+}
+
+impl ScriptError {
+    /// Make `TypeMismatch` error, with the type of `actual` value.
+    pub fn type_mismatch(expected: &'static str, actual: &ScriptValue) -> Self {
+        ScriptError::TypeMismatch {
+            path: String::new(),
+            expected,
+            actual: actual.type_name(),
+        }
+    }
+    /// Make `MissingProperty` error of object property `key`.
+    pub fn missing_property(key: &str) -> Self {
+        ScriptError::MissingProperty {
+            path: key.to_string(),
+        }
+    }
+    /// Make `Custom` error with message.
+    pub fn custom(message: impl std::fmt::Display) -> Self {
+        ScriptError::Custom {
+            path: String::new(),
+            message: message.to_string(),
+        }
+    }
+    /// Make `InvalidArgument` error of argument `index`, the function name is filled later.
+    pub(crate) fn invalid_argument(index: usize, error: ScriptError) -> Self {
+        ScriptError::InvalidArgument {
+            function: String::new(),
+            index,
+            error: Box::new(error),
+        }
+    }
+    /// Name the function of `InvalidArgument` error.
+    pub(crate) fn in_function(mut self, name: &str) -> Self {
+        if let ScriptError::InvalidArgument { function, .. } = &mut self {
+            *function = name.to_string();
+        }
+        self
+    }
+    /// Location of the failed value.
+    pub fn path(&self) -> &str {
+        match self {
+            ScriptError::TypeMismatch { path, .. }
+            | ScriptError::MissingProperty { path }
+            | ScriptError::NonIntegral { path, .. }
+            | ScriptError::OutOfRange { path, .. }
+            | ScriptError::InvalidUtf8 { path }
+            | ScriptError::Custom { path, .. } => path,
+            ScriptError::InvalidArgument { error, .. } => error.path(),
+        }
+    }
+    fn path_mut(&mut self) -> &mut String {
+        match self {
+            ScriptError::TypeMismatch { path, .. }
+            | ScriptError::MissingProperty { path }
+            | ScriptError::NonIntegral { path, .. }
+            | ScriptError::OutOfRange { path, .. }
+            | ScriptError::InvalidUtf8 { path }
+            | ScriptError::Custom { path, .. } => path,
+            ScriptError::InvalidArgument { error, .. } => error.path_mut(),
+        }
+    }
+    /// The error happened in array element `idx`.
+    pub fn at_index(mut self, idx: usize) -> Self {
+        let path = self.path_mut();
+        let sep = if path.is_empty() || path.starts_with('[') {
+            ""
+        } else {
+            "."
+        };
+        *path = format!("[{}]{}{}", idx, sep, path);
+        self
+    }
+    /// The error happened in object property `key`.
+    pub fn at_key(mut self, key: &str) -> Self {
+        let path = self.path_mut();
+        let sep = if path.is_empty() || path.starts_with('[') {
+            ""
+        } else {
+            "."
+        };
+        *path = format!("{}{}{}", key, sep, path);
+        self
+    }
+    /// Name of the JavaScript error class reporting this error.
+    pub(crate) fn js_error_name(&self) -> &'static str {
+        match self {
+            ScriptError::TypeMismatch { .. }
+            | ScriptError::MissingProperty { .. }
+            | ScriptError::InvalidUtf8 { .. }
+            | ScriptError::InvalidArgument { .. } => "TypeError",
+            ScriptError::NonIntegral { .. } | ScriptError::OutOfRange { .. } => "RangeError",
+            ScriptError::Custom { .. } => "Error",
+        }
+    }
+    /// Make JavaScript error object `{ name, message }` of this error.
+    pub(crate) fn to_js_error(&self) -> ScriptValue {
+        ScriptValue::new_error(self.js_error_name(), &self.to_string())
+    }
+}
+
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScriptError::TypeMismatch {
+                expected, actual, ..
+            } => write!(f, "type mismatch, expected {}, found {}", expected, actual)?,
+            ScriptError::MissingProperty { .. } => f.write_str("missing property")?,
+            ScriptError::NonIntegral { value, .. } => {
+                write!(f, "number {} is not an integer", value)?
+            }
+            ScriptError::OutOfRange { value, target, .. } => {
+                write!(f, "number {} out of range of {}", value, target)?
+            }
+            ScriptError::InvalidUtf8 { .. } => f.write_str("invalid UTF-8 string")?,
+            ScriptError::Custom { message, .. } => f.write_str(message)?,
+            ScriptError::InvalidArgument {
+                function,
+                index,
+                error,
+            } => {
+                // The argument error has the path.
+                return write!(f, "`{}` argument {}: {}", function, index + 1, error);
+            }
+        }
+        let path = self.path();
+        if !path.is_empty() {
+            write!(f, " at `{}`", path)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_path() {
+        let err = ScriptError::missing_property("title")
+            .at_index(3)
+            .at_key("items");
+        assert_eq!(err.path(), "items[3].title");
+        assert_eq!(err.to_string(), "missing property at `items[3].title`");
+
+        let err = ScriptError::custom("bad").at_index(1).at_index(0);
+        assert_eq!(err.path(), "[0][1]");
+
+        let err = ScriptError::invalid_argument(0, ScriptError::missing_property("title"))
+            .in_function("setTitle");
+        assert_eq!(
+            err.to_string(),
+            "`setTitle` argument 1: missing property at `title`"
+        );
+    }
+}
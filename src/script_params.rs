@@ -0,0 +1,172 @@
+//! Parameters of Rust functions exported to JavaScript.
+
+use crate::script_error::ScriptError;
+use crate::script_value::{FromScriptValue, ScriptValue};
+
+/// Arguments of a JavaScript call, consumed by parameters in order.
+#[doc(hidden)]
+pub struct ScriptArgs<'a> {
+    params: &'a [ScriptValue],
+    next: usize,
+    strict: bool,
+}
+
+impl<'a> ScriptArgs<'a> {
+    /// Take the next argument, and its index.
+    fn take(&mut self) -> (usize, Option<&'a ScriptValue>) {
+        let index = self.next;
+        self.next += 1;
+        (index, self.params.get(index))
+    }
+    /// Take all remaining arguments, and the index of the first one.
+    fn take_rest(&mut self) -> (usize, &'a [ScriptValue]) {
+        let start = self.next;
+        self.next = self.params.len().max(start);
+        (start, self.params.get(start..).unwrap_or_default())
+    }
+}
+
+/// A parameter type of exported Rust functions.
+///
+/// Implemented for `FromScriptValue + Default` types taking one argument.
+/// The last parameter may also be `Rest`, see `LastScriptParam`.
+pub trait ScriptParam: Sized {
+    #[doc(hidden)]
+    fn from_args(args: &mut ScriptArgs) -> Result<Self, ScriptError>;
+}
+
+/// Missing or invalid arguments are `Default::default()`, unless in strict mode.
+impl<T: FromScriptValue + Default> ScriptParam for T {
+    fn from_args(args: &mut ScriptArgs) -> Result<Self, ScriptError> {
+        let (index, arg) = args.take();
+        if !args.strict {
+            return Ok(arg
+                .and_then(|arg| T::from_script_value(arg).ok())
+                .unwrap_or_default());
+        }
+        match arg {
+            Some(arg) => T::from_script_value(arg),
+            // Only `Option` parameters may be omitted.
+            None => T::from_missing().ok_or(ScriptError::TypeMismatch {
+                path: String::new(),
+                expected: std::any::type_name::<T>(),
+                actual: "undefined",
+            }),
+        }
+        .map_err(|e| ScriptError::invalid_argument(index, e))
+    }
+}
+
+/// Type of the last parameter of exported Rust functions.
+///
+/// Implemented for `ScriptParam` types, and `Rest` taking the remaining arguments.
+/// `Rest` isn't a `ScriptParam`, so it can't be followed by other parameters.
+pub trait LastScriptParam: Sized {
+    #[doc(hidden)]
+    fn from_last_args(args: &mut ScriptArgs) -> Result<Self, ScriptError>;
+}
+
+impl<T: ScriptParam> LastScriptParam for T {
+    fn from_last_args(args: &mut ScriptArgs) -> Result<Self, ScriptError> {
+        T::from_args(args)
+    }
+}
+
+/// The remaining arguments, as the last parameter of exported function.
+///
+/// Invalid arguments are `InvalidArgument` errors, in strict mode or not.
+///
+/// ```no_run
+/// use kwui::{Rest, ScriptEngine};
+///
+/// fn sum(Rest(values): Rest<Vec<f64>>) -> f64 {
+///     values.iter().sum()
+/// }
+/// ScriptEngine::add_global_function("sum", sum).detach();
+/// ```
+///
+/// Other parameters can't follow it:
+///
+/// ```compile_fail
+/// use kwui::{Rest, ScriptEngine};
+///
+/// fn scale(Rest(values): Rest<Vec<f64>>, factor: f64) {}
+/// ScriptEngine::add_global_function("scale", scale).detach();
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rest<T>(pub T);
+
+impl<T: FromScriptValue> LastScriptParam for Rest<Vec<T>> {
+    fn from_last_args(args: &mut ScriptArgs) -> Result<Self, ScriptError> {
+        let (start, rest) = args.take_rest();
+        rest.iter()
+            .enumerate()
+            .map(|(i, arg)| {
+                T::from_script_value(arg).map_err(|e| ScriptError::invalid_argument(start + i, e))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Rest)
+    }
+}
+
+/// Signature marker of functions taking all arguments as `&[ScriptValue]`.
+#[doc(hidden)]
+pub struct RawArgs;
+
+pub(crate) trait ScriptFuntionParams: Sized {
+    /// In strict mode, missing, extra or invalid arguments are `InvalidArgument` errors.
+    fn from_params(params: &[ScriptValue], strict: bool) -> Result<Self, ScriptError>;
+}
+
+macro_rules! impl_script_function_param {
+    () => {
+        impl ScriptFuntionParams for () {
+            fn from_params(params: &[ScriptValue], strict: bool) -> Result<Self, ScriptError> {
+                if strict && !params.is_empty() {
+                    let msg = "unexpected argument, expected at most 0".to_string();
+                    return Err(ScriptError::invalid_argument(0, ScriptError::custom(msg)));
+                }
+                Ok(())
+            }
+        }
+    };
+    ($($pa:ident),* ; $last:ident) => {
+        impl<$($pa: ScriptParam,)* $last: LastScriptParam> ScriptFuntionParams
+            for ($($pa,)* $last,)
+        {
+            fn from_params(params: &[ScriptValue], strict: bool) -> Result<Self, ScriptError> {
+                let mut args = ScriptArgs {
+                    params,
+                    next: 0,
+                    strict,
+                };
+                let ret = ($($pa::from_args(&mut args)?,)* $last::from_last_args(&mut args)?,);
+                if strict && args.next < params.len() {
+                    let msg = format!("unexpected argument, expected at most {}", args.next);
+                    return Err(ScriptError::invalid_argument(
+                        args.next,
+                        ScriptError::custom(msg),
+                    ));
+                }
+                Ok(ret)
+            }
+        }
+    };
+}
+impl_script_function_param!();
+impl_script_function_param!(; A1);
+impl_script_function_param!(A1; A2);
+impl_script_function_param!(A1, A2; A3);
+impl_script_function_param!(A1, A2, A3; A4);
+impl_script_function_param!(A1, A2, A3, A4; A5);
+impl_script_function_param!(A1, A2, A3, A4, A5; A6);
+impl_script_function_param!(A1, A2, A3, A4, A5, A6; A7);
+impl_script_function_param!(A1, A2, A3, A4, A5, A6, A7; A8);
+impl_script_function_param!(A1, A2, A3, A4, A5, A6, A7, A8; A9);
+impl_script_function_param!(A1, A2, A3, A4, A5, A6, A7, A8, A9; A10);
+impl_script_function_param!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10; A11);
+impl_script_function_param!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11; A12);
+impl_script_function_param!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12; A13);
+impl_script_function_param!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13; A14);
+impl_script_function_param!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13, A14; A15);
+impl_script_function_param!(A1, A2, A3, A4, A5, A6, A7, A8, A9, A10, A11, A12, A13, A14, A15; A16);
@@ -0,0 +1,362 @@
+use crate::script_error::ScriptError;
+use crate::script_value::{ScriptValue, MAX_SAFE_INTEGER};
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+/// serde `Deserializer` reading from a `ScriptValue`.
+pub struct ScriptValueDeserializer<'a> {
+    value: &'a ScriptValue,
+}
+
+impl<'a> ScriptValueDeserializer<'a> {
+    pub fn new(value: &'a ScriptValue) -> Self {
+        Self { value }
+    }
+    fn invalid_type(&self, expected: &'static str) -> ScriptError {
+        ScriptError::type_mismatch(expected, self.value)
+    }
+    fn integer(&self, min: f64, end: f64, target: &'static str) -> Result<f64, ScriptError> {
+        if !self.value.is_number() {
+            return Err(self.invalid_type("integer"));
+        }
+        let v = self.value.to_double();
+        if v.fract() != 0.0 || v.is_nan() {
+            return Err(ScriptError::NonIntegral {
+                path: String::new(),
+                value: v,
+            });
+        }
+        if v < min || v >= end {
+            return Err(ScriptError::OutOfRange {
+                path: String::new(),
+                value: v,
+                target,
+            });
+        }
+        Ok(v)
+    }
+}
+
+macro_rules! deserialize_signed {
+    ($($method:ident => $ty:ident),*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ScriptError> {
+                let v = self.integer($ty::MIN as f64, $ty::MAX as f64 + 1.0, stringify!($ty))?;
+                visitor.visit_i64(v as i64)
+            }
+        )*
+    };
+}
+
+macro_rules! deserialize_unsigned {
+    ($($method:ident => $ty:ident),*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ScriptError> {
+                let v = self.integer(0.0, $ty::MAX as f64 + 1.0, stringify!($ty))?;
+                visitor.visit_u64(v as u64)
+            }
+        )*
+    };
+}
+
+impl<'de, 'a> de::Deserializer<'de> for ScriptValueDeserializer<'a> {
+    type Error = ScriptError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ScriptError> {
+        let value = self.value;
+        if value.is_null() {
+            visitor.visit_unit()
+        } else if value.is_bool() {
+            visitor.visit_bool(value.to_bool())
+        } else if value.is_number() {
+            let v = value.to_double();
+            if v.fract() == 0.0 && v.abs() <= MAX_SAFE_INTEGER {
+                if v < 0.0 {
+                    visitor.visit_i64(v as i64)
+                } else {
+                    visitor.visit_u64(v as u64)
+                }
+            } else {
+                visitor.visit_f64(v)
+            }
+        } else if value.is_string() {
+            visitor.visit_string(value.to_string())
+        } else if value.is_array() {
+            visitor.visit_seq(SeqAccess::new(value))
+        } else if value.is_object() {
+            visitor.visit_map(MapAccess::new(value))
+        } else {
+            Err(self.invalid_type("value"))
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ScriptError> {
+        if !self.value.is_bool() {
+            return Err(self.invalid_type("boolean"));
+        }
+        visitor.visit_bool(self.value.to_bool())
+    }
+
+    deserialize_signed!(
+        deserialize_i8 => i8,
+        deserialize_i16 => i16,
+        deserialize_i32 => i32,
+        deserialize_i64 => i64
+    );
+    deserialize_unsigned!(
+        deserialize_u8 => u8,
+        deserialize_u16 => u16,
+        deserialize_u32 => u32,
+        deserialize_u64 => u64
+    );
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ScriptError> {
+        self.deserialize_f64(visitor)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ScriptError> {
+        if !self.value.is_number() {
+            return Err(self.invalid_type("number"));
+        }
+        visitor.visit_f64(self.value.to_double())
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ScriptError> {
+        if self.value.is_null() || self.value.is_undefined() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ScriptError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ScriptError> {
+        let (variant, value) = crate::__private::enum_variant(self.value)?;
+        visitor.visit_enum(EnumAccess { variant, value })
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 char str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct SeqAccess<'a> {
+    array: &'a ScriptValue,
+    index: usize,
+    len: usize,
+}
+
+impl<'a> SeqAccess<'a> {
+    fn new(array: &'a ScriptValue) -> Self {
+        Self {
+            array,
+            index: 0,
+            len: array.length(),
+        }
+    }
+}
+
+impl<'de, 'a> de::SeqAccess<'de> for SeqAccess<'a> {
+    type Error = ScriptError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, ScriptError> {
+        if self.index >= self.len {
+            return Ok(None);
+        }
+        let index = self.index;
+        let value = self.array.get_value_by_index(index);
+        self.index += 1;
+        seed.deserialize(ScriptValueDeserializer::new(&value))
+            .map(Some)
+            .map_err(|e| e.at_index(index))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.index)
+    }
+}
+
+struct MapAccess<'a> {
+    object: &'a ScriptValue,
+    keys: std::vec::IntoIter<String>,
+    key: Option<String>,
+}
+
+impl<'a> MapAccess<'a> {
+    fn new(object: &'a ScriptValue) -> Self {
+        let mut keys = Vec::new();
+        object.visit_object(|k, _| keys.push(k.to_string()));
+        Self {
+            object,
+            keys: keys.into_iter(),
+            key: None,
+        }
+    }
+}
+
+impl<'de, 'a> de::MapAccess<'de> for MapAccess<'a> {
+    type Error = ScriptError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, ScriptError> {
+        match self.keys.next() {
+            Some(key) => {
+                let ret = seed.deserialize(MapKeyDeserializer { key: &key }).map(Some);
+                self.key = Some(key);
+                ret
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, ScriptError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ScriptError::custom("next_value called before next_key"))?;
+        let value = self.object.get_value_by_str(&key);
+        seed.deserialize(ScriptValueDeserializer::new(&value))
+            .map_err(|e| e.at_key(&key))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.keys.len())
+    }
+}
+
+/// Object keys are always strings, parse them when integer keys are expected.
+struct MapKeyDeserializer<'a> {
+    key: &'a str,
+}
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident => $visit:ident),*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ScriptError> {
+                match self.key.parse() {
+                    Ok(v) => visitor.$visit(v),
+                    Err(_) => visitor.visit_str(self.key),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de, 'a> de::Deserializer<'de> for MapKeyDeserializer<'a> {
+    type Error = ScriptError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ScriptError> {
+        visitor.visit_str(self.key)
+    }
+
+    deserialize_parsed_key!(
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64
+    );
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ScriptError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i128 u128 f32 f64 char str string bytes byte_buf option unit unit_struct
+        seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct EnumAccess {
+    variant: String,
+    value: Option<ScriptValue>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumAccess {
+    type Error = ScriptError;
+    type Variant = VariantAccess;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, VariantAccess), ScriptError> {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, VariantAccess { value: self.value }))
+    }
+}
+
+struct VariantAccess {
+    value: Option<ScriptValue>,
+}
+
+impl VariantAccess {
+    fn value(&self) -> Result<&ScriptValue, ScriptError> {
+        self.value
+            .as_ref()
+            .ok_or_else(|| ScriptError::custom("invalid type: unit variant, expected payload"))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for VariantAccess {
+    type Error = ScriptError;
+
+    fn unit_variant(self) -> Result<(), ScriptError> {
+        match self.value {
+            Some(value) if !value.is_null() => Err(ScriptError::custom(
+                "invalid type: payload, expected unit variant",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, ScriptError> {
+        seed.deserialize(ScriptValueDeserializer::new(self.value()?))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, ScriptError> {
+        de::Deserializer::deserialize_seq(ScriptValueDeserializer::new(self.value()?), visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ScriptError> {
+        de::Deserializer::deserialize_map(ScriptValueDeserializer::new(self.value()?), visitor)
+    }
+}
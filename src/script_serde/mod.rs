@@ -0,0 +1,89 @@
+//! serde data format over `ScriptValue`, enabled by the `serde` feature.
+//!
+//! Structs and maps become JavaScript objects, sequences and tuples become arrays,
+//! `None` and unit become `null`. Enums are externally tagged: unit variants become
+//! strings, other variants become `{ "Variant": payload }` objects.
+
+mod de;
+mod ser;
+
+pub use de::ScriptValueDeserializer;
+pub use ser::ScriptValueSerializer;
+
+use crate::script_error::ScriptError;
+use crate::script_value::ScriptValue;
+use serde::{de::DeserializeOwned, Serialize};
+
+impl serde::ser::Error for ScriptError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        ScriptError::custom(msg)
+    }
+}
+
+impl serde::de::Error for ScriptError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        ScriptError::custom(msg)
+    }
+    fn missing_field(field: &'static str) -> Self {
+        ScriptError::missing_property(field)
+    }
+}
+
+/// Convert a `T` to `ScriptValue` with serde.
+pub fn to_script_value<T: Serialize + ?Sized>(value: &T) -> Result<ScriptValue, ScriptError> {
+    value.serialize(ScriptValueSerializer)
+}
+
+/// Convert a `ScriptValue` to `T` with serde.
+pub fn from_script_value<T: DeserializeOwned>(value: &ScriptValue) -> Result<T, ScriptError> {
+    T::deserialize(ScriptValueDeserializer::new(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Kind {
+        Plain,
+        Sized(u32),
+        Point { x: i32, y: i32 },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Item {
+        display_name: String,
+        size: Option<f64>,
+        tags: Vec<String>,
+        kinds: Vec<Kind>,
+        extra: HashMap<String, bool>,
+    }
+
+    #[test]
+    fn round_trip() {
+        let item = Item {
+            display_name: "a".to_string(),
+            size: None,
+            tags: vec!["x".to_string(), "y".to_string()],
+            kinds: vec![Kind::Plain, Kind::Sized(3), Kind::Point { x: -1, y: 2 }],
+            extra: [("k".to_string(), true)].into_iter().collect(),
+        };
+        let v = to_script_value(&item).unwrap();
+        assert!(v.get_value_by_str("displayName").is_string());
+        assert!(v.get_value_by_str("size").is_null());
+        assert_eq!(from_script_value::<Item>(&v), Ok(item));
+    }
+
+    #[test]
+    fn missing_field() {
+        let mut v = ScriptValue::new_object();
+        v.set_by_str("displayName", "a");
+        let err = from_script_value::<Item>(&v).unwrap_err();
+        assert_eq!(err, ScriptError::missing_property("tags"));
+        let absent = v.get_value_by_str("size");
+        assert_eq!(from_script_value::<Option<f64>>(&absent), Ok(None));
+    }
+}
@@ -0,0 +1,442 @@
+use crate::__private::new_tagged;
+use crate::script_error::ScriptError;
+use crate::script_value::{IntoScriptValue, ScriptValue};
+use serde::ser::{self, Serialize};
+
+/// serde `Serializer` producing `ScriptValue`.
+pub struct ScriptValueSerializer;
+
+fn convert(v: impl IntoScriptValue) -> Result<ScriptValue, ScriptError> {
+    v.into_script_value()
+}
+
+impl ser::Serializer for ScriptValueSerializer {
+    type Ok = ScriptValue;
+    type Error = ScriptError;
+
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeTupleVariant;
+    type SerializeMap = SerializeObject;
+    type SerializeStruct = SerializeObject;
+    type SerializeStructVariant = SerializeStructVariant;
+
+    fn serialize_bool(self, v: bool) -> Result<ScriptValue, ScriptError> {
+        convert(v)
+    }
+    fn serialize_i8(self, v: i8) -> Result<ScriptValue, ScriptError> {
+        convert(v)
+    }
+    fn serialize_i16(self, v: i16) -> Result<ScriptValue, ScriptError> {
+        convert(v)
+    }
+    fn serialize_i32(self, v: i32) -> Result<ScriptValue, ScriptError> {
+        convert(v)
+    }
+    fn serialize_i64(self, v: i64) -> Result<ScriptValue, ScriptError> {
+        convert(v)
+    }
+    fn serialize_u8(self, v: u8) -> Result<ScriptValue, ScriptError> {
+        convert(v)
+    }
+    fn serialize_u16(self, v: u16) -> Result<ScriptValue, ScriptError> {
+        convert(v)
+    }
+    fn serialize_u32(self, v: u32) -> Result<ScriptValue, ScriptError> {
+        convert(v)
+    }
+    fn serialize_u64(self, v: u64) -> Result<ScriptValue, ScriptError> {
+        convert(v)
+    }
+    fn serialize_f32(self, v: f32) -> Result<ScriptValue, ScriptError> {
+        convert(v)
+    }
+    fn serialize_f64(self, v: f64) -> Result<ScriptValue, ScriptError> {
+        convert(v)
+    }
+    fn serialize_char(self, v: char) -> Result<ScriptValue, ScriptError> {
+        convert(v.encode_utf8(&mut [0; 4]) as &str)
+    }
+    fn serialize_str(self, v: &str) -> Result<ScriptValue, ScriptError> {
+        convert(v)
+    }
+    fn serialize_bytes(self, v: &[u8]) -> Result<ScriptValue, ScriptError> {
+        let arr = ScriptValue::new_array();
+        for (i, b) in v.iter().enumerate() {
+            arr.set_value_by_index(i, convert(*b)?);
+        }
+        Ok(arr)
+    }
+    fn serialize_none(self) -> Result<ScriptValue, ScriptError> {
+        Ok(ScriptValue::new_null())
+    }
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<ScriptValue, ScriptError> {
+        value.serialize(self)
+    }
+    fn serialize_unit(self) -> Result<ScriptValue, ScriptError> {
+        Ok(ScriptValue::new_null())
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<ScriptValue, ScriptError> {
+        Ok(ScriptValue::new_null())
+    }
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<ScriptValue, ScriptError> {
+        convert(variant)
+    }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<ScriptValue, ScriptError> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<ScriptValue, ScriptError> {
+        Ok(new_tagged(variant, value.serialize(self)?))
+    }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SerializeArray, ScriptError> {
+        Ok(SerializeArray::new())
+    }
+    fn serialize_tuple(self, _len: usize) -> Result<SerializeArray, ScriptError> {
+        Ok(SerializeArray::new())
+    }
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<SerializeArray, ScriptError> {
+        Ok(SerializeArray::new())
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeTupleVariant, ScriptError> {
+        Ok(SerializeTupleVariant {
+            variant,
+            array: SerializeArray::new(),
+        })
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeObject, ScriptError> {
+        Ok(SerializeObject::new())
+    }
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<SerializeObject, ScriptError> {
+        Ok(SerializeObject::new())
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeStructVariant, ScriptError> {
+        Ok(SerializeStructVariant {
+            variant,
+            object: SerializeObject::new(),
+        })
+    }
+}
+
+#[doc(hidden)]
+pub struct SerializeArray {
+    array: ScriptValue,
+    len: usize,
+}
+
+impl SerializeArray {
+    fn new() -> Self {
+        Self {
+            array: ScriptValue::new_array(),
+            len: 0,
+        }
+    }
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ScriptError> {
+        let value = value
+            .serialize(ScriptValueSerializer)
+            .map_err(|e| e.at_index(self.len))?;
+        self.array.set_value_by_index(self.len, value);
+        self.len += 1;
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = ScriptValue;
+    type Error = ScriptError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ScriptError> {
+        self.push(value)
+    }
+    fn end(self) -> Result<ScriptValue, ScriptError> {
+        Ok(self.array)
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = ScriptValue;
+    type Error = ScriptError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ScriptError> {
+        self.push(value)
+    }
+    fn end(self) -> Result<ScriptValue, ScriptError> {
+        Ok(self.array)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = ScriptValue;
+    type Error = ScriptError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ScriptError> {
+        self.push(value)
+    }
+    fn end(self) -> Result<ScriptValue, ScriptError> {
+        Ok(self.array)
+    }
+}
+
+#[doc(hidden)]
+pub struct SerializeTupleVariant {
+    variant: &'static str,
+    array: SerializeArray,
+}
+
+impl ser::SerializeTupleVariant for SerializeTupleVariant {
+    type Ok = ScriptValue;
+    type Error = ScriptError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ScriptError> {
+        self.array.push(value)
+    }
+    fn end(self) -> Result<ScriptValue, ScriptError> {
+        Ok(new_tagged(self.variant, self.array.array))
+    }
+}
+
+#[doc(hidden)]
+pub struct SerializeObject {
+    object: ScriptValue,
+    key: Option<String>,
+}
+
+impl SerializeObject {
+    fn new() -> Self {
+        Self {
+            object: ScriptValue::new_object(),
+            key: None,
+        }
+    }
+    fn insert<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), ScriptError> {
+        let value = value
+            .serialize(ScriptValueSerializer)
+            .map_err(|e| e.at_key(key))?;
+        self.object.set_value_by_str(key, value);
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for SerializeObject {
+    type Ok = ScriptValue;
+    type Error = ScriptError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), ScriptError> {
+        self.key = Some(key.serialize(MapKeySerializer)?);
+        Ok(())
+    }
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ScriptError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ScriptError::custom("serialize_value called before serialize_key"))?;
+        self.insert(&key, value)
+    }
+    fn end(self) -> Result<ScriptValue, ScriptError> {
+        Ok(self.object)
+    }
+}
+
+impl ser::SerializeStruct for SerializeObject {
+    type Ok = ScriptValue;
+    type Error = ScriptError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ScriptError> {
+        self.insert(key, value)
+    }
+    fn end(self) -> Result<ScriptValue, ScriptError> {
+        Ok(self.object)
+    }
+}
+
+#[doc(hidden)]
+pub struct SerializeStructVariant {
+    variant: &'static str,
+    object: SerializeObject,
+}
+
+impl ser::SerializeStructVariant for SerializeStructVariant {
+    type Ok = ScriptValue;
+    type Error = ScriptError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ScriptError> {
+        self.object.insert(key, value)
+    }
+    fn end(self) -> Result<ScriptValue, ScriptError> {
+        Ok(new_tagged(self.variant, self.object.object))
+    }
+}
+
+/// Object keys are always strings, accept strings, chars and integers.
+struct MapKeySerializer;
+
+fn key_must_be_a_string() -> ScriptError {
+    ScriptError::custom("object key must be a string")
+}
+
+macro_rules! serialize_key_to_string {
+    ($($method:ident: $ty:ty),*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<String, ScriptError> {
+                Ok(v.to_string())
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for MapKeySerializer {
+    type Ok = String;
+    type Error = ScriptError;
+
+    type SerializeSeq = ser::Impossible<String, ScriptError>;
+    type SerializeTuple = ser::Impossible<String, ScriptError>;
+    type SerializeTupleStruct = ser::Impossible<String, ScriptError>;
+    type SerializeTupleVariant = ser::Impossible<String, ScriptError>;
+    type SerializeMap = ser::Impossible<String, ScriptError>;
+    type SerializeStruct = ser::Impossible<String, ScriptError>;
+    type SerializeStructVariant = ser::Impossible<String, ScriptError>;
+
+    serialize_key_to_string!(
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_char: char,
+        serialize_str: &str
+    );
+
+    fn serialize_f32(self, _v: f32) -> Result<String, ScriptError> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_f64(self, _v: f64) -> Result<String, ScriptError> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String, ScriptError> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_none(self) -> Result<String, ScriptError> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<String, ScriptError> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_unit(self) -> Result<String, ScriptError> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, ScriptError> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String, ScriptError> {
+        Ok(variant.to_string())
+    }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, ScriptError> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, ScriptError> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, ScriptError> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, ScriptError> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, ScriptError> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, ScriptError> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, ScriptError> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, ScriptError> {
+        Err(key_must_be_a_string())
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, ScriptError> {
+        Err(key_must_be_a_string())
+    }
+}
@@ -1,7 +1,12 @@
+use crate::script_error::ScriptError;
 use kwui_sys::*;
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::ffi::{CStr, CString};
+use std::hash::Hash;
 use std::os::raw::{c_int, c_void};
+use std::rc::Rc;
+use std::sync::Arc;
 
 /// Wraps JavaScript value
 pub struct ScriptValue {
@@ -53,6 +58,15 @@ impl ScriptValue {
         let inner = unsafe { kwui_ScriptValue_newObject() };
         Self { inner }
     }
+    /// Make an error object `{ name, message }`, `name` is the error class, like "TypeError".
+    ///
+    /// NOTE: it's a plain object, not an `instanceof Error`.
+    pub fn new_error(name: &str, message: &str) -> Self {
+        let mut err = Self::new_object();
+        err.set_by_str("name", name);
+        err.set_by_str("message", message);
+        err
+    }
     /// Retrieve value of sub-element at `idx`.
     pub fn get_value_by_index(&self, idx: usize) -> ScriptValue {
         let inner = unsafe { kwui_ScriptValue_get_by_index(self.inner, idx as _) };
@@ -63,8 +77,8 @@ impl ScriptValue {
         T::from_script_value(&self.get_value_by_index(idx)).unwrap_or(T::default())
     }
     /// Try to retrieve value of sub-element at `idx`, then convert to `T` type.
-    pub fn try_get_by_index<T: FromScriptValue>(&self, idx: usize) -> Result<T, ()> {
-        T::from_script_value(&self.get_value_by_index(idx))
+    pub fn try_get_by_index<T: FromScriptValue>(&self, idx: usize) -> Result<T, ScriptError> {
+        T::from_script_value(&self.get_value_by_index(idx)).map_err(|e| e.at_index(idx))
     }
     /// Insert or set value of the sub-element by `idx`.
     pub fn set_value_by_index(&self, idx: usize, v: ScriptValue) {
@@ -87,8 +101,8 @@ impl ScriptValue {
         T::from_script_value(&self.get_value_by_str(key)).unwrap_or_default()
     }
     /// Try to retrieve value of sub-element at `idx`, then convert to `T` type.
-    pub fn try_get_by_str<T: FromScriptValue>(&self, key: &str) -> Result<T, ()> {
-        T::from_script_value(&self.get_value_by_str(key))
+    pub fn try_get_by_str<T: FromScriptValue>(&self, key: &str) -> Result<T, ScriptError> {
+        T::from_script_value(&self.get_value_by_str(key)).map_err(|e| e.at_key(key))
     }
     /// Insert or set value of the sub-element by `key`.
     pub fn set_by_str(&mut self, key: &str, v: impl IntoScriptValue) {
@@ -120,6 +134,33 @@ impl ScriptValue {
     pub fn is_object(&self) -> bool {
         unsafe { kwui_ScriptValue_is_object(self.inner) }
     }
+    /// NOTE: the kwui C API has no `undefined` check, it's a value of none of the other types.
+    pub fn is_undefined(&self) -> bool {
+        !(self.is_null()
+            || self.is_bool()
+            || self.is_number()
+            || self.is_string()
+            || self.is_array()
+            || self.is_object())
+    }
+    /// JavaScript type name of the value, for error messages.
+    pub fn type_name(&self) -> &'static str {
+        if self.is_null() {
+            "null"
+        } else if self.is_bool() {
+            "boolean"
+        } else if self.is_number() {
+            "number"
+        } else if self.is_string() {
+            "string"
+        } else if self.is_array() {
+            "array"
+        } else if self.is_object() {
+            "object"
+        } else {
+            "undefined"
+        }
+    }
     /// Value to boolean.
     pub fn to_bool(&self) -> bool {
         unsafe { kwui_ScriptValue_to_bool(self.inner) }
@@ -146,46 +187,54 @@ impl ScriptValue {
         unsafe { kwui_ScriptValue_length(self.inner) }
     }
     /// Visiting all values of an array.
+    ///
+    /// A panic in `callback` stops visiting, and resumes after leaving the native side.
     pub fn visit_array<F: FnMut(usize, &ScriptValue)>(&self, callback: F) {
-        //let callback = Box::into_raw(Box::new(callback)) as *mut c_void;
         unsafe extern "C" fn visit_array_callback<F: FnMut(usize, &ScriptValue)>(
             index: c_int,
             val: *const kwui_ScriptValue,
             udata: *mut c_void,
         ) {
+            let visitor = &mut *(udata as *mut Visitor<F>);
             let val = ScriptValue::from_inner(val as _);
-            (*(udata as *mut F))(index as _, &val);
+            visitor.visit(|f| f(index as _, &val));
             std::mem::forget(val);
         }
+        let mut visitor = Visitor::new(callback);
         unsafe {
             kwui_ScriptValue_visitArray(
                 self.inner,
                 Some(visit_array_callback::<F>),
-                &callback as *const F as _,
+                &mut visitor as *mut Visitor<F> as _,
             );
         }
+        visitor.resume_panic();
     }
     /// Visiting all values of an object, in arbitrary order.
+    ///
+    /// A panic in `callback` stops visiting, and resumes after leaving the native side.
     pub fn visit_object<F: FnMut(&str, &ScriptValue)>(&self, callback: F) {
-        //let callback = Box::into_raw(Box::new(callback)) as *mut c_void;
         unsafe extern "C" fn visit_object_callback<F: FnMut(&str, &ScriptValue)>(
             key: *const std::os::raw::c_char,
             key_len: usize,
             val: *const kwui_ScriptValue,
             udata: *mut c_void,
         ) {
+            let visitor = &mut *(udata as *mut Visitor<F>);
             let key = std::slice::from_raw_parts(key as *const u8, key_len);
             let val = ScriptValue::from_inner(val as _);
-            (*(udata as *mut F))(std::str::from_utf8_unchecked(key), &val);
+            visitor.visit(|f| f(std::str::from_utf8_unchecked(key), &val));
             std::mem::forget(val);
         }
+        let mut visitor = Visitor::new(callback);
         unsafe {
             kwui_ScriptValue_visitObject(
                 self.inner,
                 Some(visit_object_callback::<F>),
-                &callback as *const F as _,
+                &mut visitor as *mut Visitor<F> as _,
             );
         }
+        visitor.resume_panic();
     }
 
     pub(crate) fn from_inner(inner: *mut kwui_ScriptValue) -> Self {
@@ -212,6 +261,34 @@ impl Drop for ScriptValue {
     }
 }
 
+/// Callback of `visit_array` and `visit_object`, holding its panic until the visit returns.
+struct Visitor<F> {
+    callback: F,
+    panic: Option<Box<dyn std::any::Any + Send>>,
+}
+
+impl<F> Visitor<F> {
+    fn new(callback: F) -> Self {
+        Self {
+            callback,
+            panic: None,
+        }
+    }
+    fn visit(&mut self, f: impl FnOnce(&mut F)) {
+        if self.panic.is_some() {
+            return;
+        }
+        let callback = &mut self.callback;
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| f(callback)));
+        self.panic = result.err();
+    }
+    fn resume_panic(self) {
+        if let Some(payload) = self.panic {
+            std::panic::resume_unwind(payload);
+        }
+    }
+}
+
 impl std::fmt::Debug for ScriptValue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if self.is_null() {
@@ -243,12 +320,17 @@ impl std::fmt::Debug for ScriptValue {
 
 /// Convert `Script` to rust type
 pub trait FromScriptValue: Sized {
-    fn from_script_value(value: &ScriptValue) -> Result<Self, ()>;
+    fn from_script_value(value: &ScriptValue) -> Result<Self, ScriptError>;
+    /// Value of an omitted argument, `None` if the argument is required.
+    #[doc(hidden)]
+    fn from_missing() -> Option<Self> {
+        None
+    }
 }
 
 /// Convert rust type to `ScriptValue`
 pub trait IntoScriptValue {
-    fn into_script_value(self) -> Result<ScriptValue, ()>;
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError>;
 }
 
 impl Default for ScriptValue {
@@ -257,62 +339,112 @@ impl Default for ScriptValue {
         Self { inner }
     }
 }
+impl IntoScriptValue for ScriptValue {
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
+        Ok(self)
+    }
+}
 impl FromScriptValue for () {
-    fn from_script_value(value: &ScriptValue) -> Result<Self, ()> {
+    fn from_script_value(value: &ScriptValue) -> Result<Self, ScriptError> {
         Ok(())
     }
 }
 
 impl IntoScriptValue for () {
-    fn into_script_value(self) -> Result<ScriptValue, ()> {
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
         let inner = unsafe { kwui_ScriptValue_newNull() };
         Ok(ScriptValue::from_inner(inner))
     }
 }
 impl FromScriptValue for bool {
-    fn from_script_value(value: &ScriptValue) -> Result<Self, ()> {
+    fn from_script_value(value: &ScriptValue) -> Result<Self, ScriptError> {
+        if !value.is_bool() {
+            return Err(ScriptError::type_mismatch("boolean", value));
+        }
         unsafe { Ok(kwui_ScriptValue_to_bool(value.inner)) }
     }
 }
 
 impl IntoScriptValue for bool {
-    fn into_script_value(self) -> Result<ScriptValue, ()> {
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
         let inner = unsafe { kwui_ScriptValue_newBool(self) };
         Ok(ScriptValue::from_inner(inner))
     }
 }
 
-macro_rules! number_from_script_value {
+/// Number value of `value`, or `TypeMismatch` error.
+fn number_value(value: &ScriptValue) -> Result<f64, ScriptError> {
+    if !value.is_number() {
+        return Err(ScriptError::type_mismatch("number", value));
+    }
+    Ok(value.to_double())
+}
+
+/// Largest integer a double represents exactly.
+pub(crate) const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
+
+macro_rules! integer_from_script_value {
     ($ty:ident) => {
         impl FromScriptValue for $ty {
-            fn from_script_value(value: &ScriptValue) -> Result<Self, ()> {
-                unsafe { Ok(kwui_ScriptValue_to_double(value.inner) as _) }
+            fn from_script_value(value: &ScriptValue) -> Result<Self, ScriptError> {
+                let v = number_value(value)?;
+                if v.fract() != 0.0 || v.is_nan() {
+                    return Err(ScriptError::NonIntegral {
+                        path: String::new(),
+                        value: v,
+                    });
+                }
+                // `MAX as f64 + 1.0` is exact power of 2, while `MAX as f64` may round up.
+                if v < $ty::MIN as f64 || v >= $ty::MAX as f64 + 1.0 {
+                    return Err(ScriptError::OutOfRange {
+                        path: String::new(),
+                        value: v,
+                        target: stringify!($ty),
+                    });
+                }
+                Ok(v as $ty)
             }
         }
     };
 }
+impl FromScriptValue for f64 {
+    fn from_script_value(value: &ScriptValue) -> Result<Self, ScriptError> {
+        number_value(value)
+    }
+}
+impl FromScriptValue for f32 {
+    fn from_script_value(value: &ScriptValue) -> Result<Self, ScriptError> {
+        let v = number_value(value)?;
+        if v.is_finite() && v.abs() > f32::MAX as f64 {
+            return Err(ScriptError::OutOfRange {
+                path: String::new(),
+                value: v,
+                target: "f32",
+            });
+        }
+        Ok(v as f32)
+    }
+}
 macro_rules! number_into_script_value {
     ($ty:ident) => {
         impl IntoScriptValue for $ty {
-            fn into_script_value(self) -> Result<ScriptValue, ()> {
+            fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
                 let inner = unsafe { kwui_ScriptValue_newDouble(self as _) };
                 Ok(ScriptValue::from_inner(inner))
             }
         }
     };
 }
-number_from_script_value!(i8);
-number_from_script_value!(u8);
-number_from_script_value!(i16);
-number_from_script_value!(u16);
-number_from_script_value!(i32);
-number_from_script_value!(u32);
-number_from_script_value!(i64);
-number_from_script_value!(u64);
-number_from_script_value!(isize);
-number_from_script_value!(usize);
-number_from_script_value!(f32);
-number_from_script_value!(f64);
+integer_from_script_value!(i8);
+integer_from_script_value!(u8);
+integer_from_script_value!(i16);
+integer_from_script_value!(u16);
+integer_from_script_value!(i32);
+integer_from_script_value!(u32);
+integer_from_script_value!(i64);
+integer_from_script_value!(u64);
+integer_from_script_value!(isize);
+integer_from_script_value!(usize);
 
 number_into_script_value!(i8);
 number_into_script_value!(u8);
@@ -327,55 +459,359 @@ number_into_script_value!(usize);
 number_into_script_value!(f32);
 number_into_script_value!(f64);
 
+/// Number converted with `as` casting, for opting out of range checking.
+///
+/// Any value is accepted: `NaN` becomes 0, `-1` becomes `u32::MAX` and `1.7` becomes 1.
+///
+/// ```no_run
+/// use kwui::{Lossy, ScriptEngine};
+///
+/// fn set_volume(volume: Lossy<u8>) {
+///     let volume: u8 = volume.0;
+/// }
+/// ScriptEngine::add_global_function("setVolume", set_volume).detach();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Lossy<T>(pub T);
+
+impl<T> Lossy<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+macro_rules! lossy_number_script_value {
+    ($($ty:ident),*) => {
+        $(
+            impl FromScriptValue for Lossy<$ty> {
+                fn from_script_value(value: &ScriptValue) -> Result<Self, ScriptError> {
+                    Ok(Lossy(value.to_double() as $ty))
+                }
+            }
+
+            impl IntoScriptValue for Lossy<$ty> {
+                fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
+                    Ok(ScriptValue::new_double(self.0 as f64))
+                }
+            }
+        )*
+    };
+}
+lossy_number_script_value!(i8, u8, i16, u16, i32, u32, i64, u64, isize, usize, f32, f64);
+
 impl FromScriptValue for String {
-    fn from_script_value(value: &ScriptValue) -> Result<Self, ()> {
-        unsafe {
+    fn from_script_value(value: &ScriptValue) -> Result<Self, ScriptError> {
+        if !value.is_string() {
+            return Err(ScriptError::type_mismatch("string", value));
+        }
+        let slice = unsafe {
             let mut len = 0;
             let data = kwui_ScriptValue_to_string(value.inner, &mut len);
-            let slice = std::slice::from_raw_parts(data as *const u8, len);
-            Ok(String::from_utf8_lossy(slice).to_string())
-        }
+            std::slice::from_raw_parts(data as *const u8, len)
+        };
+        String::from_utf8(slice.to_vec()).map_err(|_| ScriptError::InvalidUtf8 {
+            path: String::new(),
+        })
     }
 }
 
 impl IntoScriptValue for String {
-    fn into_script_value(self) -> Result<ScriptValue, ()> {
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
         let inner = unsafe { kwui_ScriptValue_newString(self.as_ptr() as _, self.len()) };
         Ok(ScriptValue::from_inner(inner))
     }
 }
 
 impl IntoScriptValue for &str {
-    fn into_script_value(self) -> Result<ScriptValue, ()> {
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
         let inner = unsafe { kwui_ScriptValue_newString(self.as_ptr() as _, self.len()) };
         Ok(ScriptValue::from_inner(inner))
     }
 }
 
-impl<K: From<String> + Eq + std::hash::Hash, V: FromScriptValue + Default> FromScriptValue
-    for HashMap<K, V>
-{
-    fn from_script_value(value: &ScriptValue) -> Result<Self, ()> {
-        let mut obj = Self::new();
-        value.visit_object(|k, v| {
-            let k = k.to_string().into();
-            let v = V::from_script_value(v).unwrap_or_default();
-            obj.insert(k, v);
-        });
-        Ok(obj)
+impl FromScriptValue for char {
+    fn from_script_value(value: &ScriptValue) -> Result<Self, ScriptError> {
+        let s = String::from_script_value(value)?;
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(ScriptError::custom(format!(
+                "expected single character string, found {:?}",
+                s
+            ))),
+        }
     }
 }
 
-impl<K: AsRef<str>, V: IntoScriptValue> IntoScriptValue for HashMap<K, V> {
-    fn into_script_value(self) -> Result<ScriptValue, ()> {
-        let mut obj = ScriptValue::new_object();
-        for (k, v) in self.into_iter() {
-            obj.set_value_by_str(k.as_ref(), v.into_script_value()?);
+impl IntoScriptValue for char {
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
+        Ok(ScriptValue::new_string(self.encode_utf8(&mut [0; 4])))
+    }
+}
+
+impl FromScriptValue for Cow<'static, str> {
+    fn from_script_value(value: &ScriptValue) -> Result<Self, ScriptError> {
+        String::from_script_value(value).map(Cow::Owned)
+    }
+}
+
+impl IntoScriptValue for Cow<'_, str> {
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
+        Ok(ScriptValue::new_string(&self))
+    }
+}
+
+/// `null` and `undefined` are `None`.
+impl<T: FromScriptValue> FromScriptValue for Option<T> {
+    fn from_script_value(value: &ScriptValue) -> Result<Self, ScriptError> {
+        if value.is_null() || value.is_undefined() {
+            Ok(None)
+        } else {
+            T::from_script_value(value).map(Some)
         }
-        Ok(obj)
+    }
+    fn from_missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+/// `None` is `null`.
+impl<T: IntoScriptValue> IntoScriptValue for Option<T> {
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
+        match self {
+            Some(v) => v.into_script_value(),
+            None => Ok(ScriptValue::new_null()),
+        }
+    }
+}
+
+/// `Err` is an error object `{ name, message }`, like failed exported functions return.
+impl<T: IntoScriptValue, E: Into<ScriptError>> IntoScriptValue for Result<T, E> {
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
+        match self {
+            Ok(v) => v.into_script_value(),
+            Err(e) => Ok(e.into().to_js_error()),
+        }
+    }
+}
+
+impl<T: FromScriptValue> FromScriptValue for Box<T> {
+    fn from_script_value(value: &ScriptValue) -> Result<Self, ScriptError> {
+        T::from_script_value(value).map(Box::new)
+    }
+}
+
+impl<T: IntoScriptValue> IntoScriptValue for Box<T> {
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
+        (*self).into_script_value()
     }
 }
 
+impl<T: FromScriptValue> FromScriptValue for Rc<T> {
+    fn from_script_value(value: &ScriptValue) -> Result<Self, ScriptError> {
+        T::from_script_value(value).map(Rc::new)
+    }
+}
+
+/// Clones the inner value if it's shared.
+impl<T: IntoScriptValue + Clone> IntoScriptValue for Rc<T> {
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
+        Rc::try_unwrap(self)
+            .unwrap_or_else(|rc| (*rc).clone())
+            .into_script_value()
+    }
+}
+
+impl<T: FromScriptValue> FromScriptValue for Arc<T> {
+    fn from_script_value(value: &ScriptValue) -> Result<Self, ScriptError> {
+        T::from_script_value(value).map(Arc::new)
+    }
+}
+
+/// Clones the inner value if it's shared.
+impl<T: IntoScriptValue + Clone> IntoScriptValue for Arc<T> {
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
+        Arc::try_unwrap(self)
+            .unwrap_or_else(|arc| (*arc).clone())
+            .into_script_value()
+    }
+}
+
+/// Make array from items.
+fn array_from_iter<T: IntoScriptValue>(
+    iter: impl IntoIterator<Item = T>,
+) -> Result<ScriptValue, ScriptError> {
+    let arr = ScriptValue::new_array();
+    for (i, v) in iter.into_iter().enumerate() {
+        let v = v.into_script_value().map_err(|e| e.at_index(i))?;
+        arr.set_value_by_index(i, v);
+    }
+    Ok(arr)
+}
+
+/// Convert all items of array.
+fn array_items<T: FromScriptValue, C: FromIterator<T>>(
+    value: &ScriptValue,
+) -> Result<C, ScriptError> {
+    if !value.is_array() {
+        return Err(ScriptError::type_mismatch("array", value));
+    }
+    (0..value.length())
+        .map(|i| value.try_get_by_index(i))
+        .collect()
+}
+
+/// Make object from key-value pairs.
+fn object_from_iter<K: AsRef<str>, V: IntoScriptValue>(
+    iter: impl IntoIterator<Item = (K, V)>,
+) -> Result<ScriptValue, ScriptError> {
+    let mut obj = ScriptValue::new_object();
+    for (k, v) in iter.into_iter() {
+        let v = v.into_script_value().map_err(|e| e.at_key(k.as_ref()))?;
+        obj.set_value_by_str(k.as_ref(), v);
+    }
+    Ok(obj)
+}
+
+/// Convert all properties of object.
+fn object_entries<K: From<String>, V: FromScriptValue, C: FromIterator<(K, V)>>(
+    value: &ScriptValue,
+) -> Result<C, ScriptError> {
+    if !value.is_object() {
+        return Err(ScriptError::type_mismatch("object", value));
+    }
+    let mut entries = Vec::with_capacity(value.length());
+    let mut err = None;
+    value.visit_object(|k, v| {
+        if err.is_some() {
+            return;
+        }
+        match V::from_script_value(v) {
+            Ok(v) => entries.push((k.to_string().into(), v)),
+            Err(e) => err = Some(e.at_key(k)),
+        }
+    });
+    match err {
+        Some(e) => Err(e),
+        None => Ok(entries.into_iter().collect()),
+    }
+}
+
+impl<T: FromScriptValue> FromScriptValue for Vec<T> {
+    fn from_script_value(value: &ScriptValue) -> Result<Self, ScriptError> {
+        array_items(value)
+    }
+}
+
+impl<T: IntoScriptValue> IntoScriptValue for Vec<T> {
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
+        array_from_iter(self)
+    }
+}
+
+impl<T: IntoScriptValue + Clone> IntoScriptValue for &[T] {
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
+        array_from_iter(self.iter().cloned())
+    }
+}
+
+impl<T: FromScriptValue, const N: usize> FromScriptValue for [T; N] {
+    fn from_script_value(value: &ScriptValue) -> Result<Self, ScriptError> {
+        let items: Vec<T> = array_items(value)?;
+        let len = items.len();
+        items.try_into().map_err(|_| {
+            ScriptError::custom(format!("expected array of length {}, found {}", N, len))
+        })
+    }
+}
+
+impl<T: IntoScriptValue, const N: usize> IntoScriptValue for [T; N] {
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
+        array_from_iter(self)
+    }
+}
+
+impl<T: FromScriptValue + Eq + Hash> FromScriptValue for HashSet<T> {
+    fn from_script_value(value: &ScriptValue) -> Result<Self, ScriptError> {
+        array_items(value)
+    }
+}
+
+impl<T: IntoScriptValue> IntoScriptValue for HashSet<T> {
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
+        array_from_iter(self)
+    }
+}
+
+impl<T: FromScriptValue + Ord> FromScriptValue for BTreeSet<T> {
+    fn from_script_value(value: &ScriptValue) -> Result<Self, ScriptError> {
+        array_items(value)
+    }
+}
+
+impl<T: IntoScriptValue> IntoScriptValue for BTreeSet<T> {
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
+        array_from_iter(self)
+    }
+}
+
+impl<K: From<String> + Eq + Hash, V: FromScriptValue> FromScriptValue for HashMap<K, V> {
+    fn from_script_value(value: &ScriptValue) -> Result<Self, ScriptError> {
+        object_entries(value)
+    }
+}
+
+impl<K: AsRef<str>, V: IntoScriptValue> IntoScriptValue for HashMap<K, V> {
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
+        object_from_iter(self)
+    }
+}
+
+impl<K: From<String> + Ord, V: FromScriptValue> FromScriptValue for BTreeMap<K, V> {
+    fn from_script_value(value: &ScriptValue) -> Result<Self, ScriptError> {
+        object_entries(value)
+    }
+}
+
+impl<K: AsRef<str>, V: IntoScriptValue> IntoScriptValue for BTreeMap<K, V> {
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
+        object_from_iter(self)
+    }
+}
+
+/// Tuples are arrays.
+macro_rules! tuple_script_value {
+    ($($idx:tt $ty:ident),+) => {
+        impl<$($ty: FromScriptValue),+> FromScriptValue for ($($ty,)+) {
+            fn from_script_value(value: &ScriptValue) -> Result<Self, ScriptError> {
+                if !value.is_array() {
+                    return Err(ScriptError::type_mismatch("array", value));
+                }
+                Ok(($(value.try_get_by_index::<$ty>($idx)?,)+))
+            }
+        }
+
+        impl<$($ty: IntoScriptValue),+> IntoScriptValue for ($($ty,)+) {
+            fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
+                let arr = ScriptValue::new_array();
+                $(
+                    let v = self.$idx.into_script_value().map_err(|e| e.at_index($idx))?;
+                    arr.set_value_by_index($idx, v);
+                )+
+                Ok(arr)
+            }
+        }
+    };
+}
+tuple_script_value!(0 T0);
+tuple_script_value!(0 T0, 1 T1);
+tuple_script_value!(0 T0, 1 T1, 2 T2);
+tuple_script_value!(0 T0, 1 T1, 2 T2, 3 T3);
+tuple_script_value!(0 T0, 1 T1, 2 T2, 3 T3, 4 T4);
+tuple_script_value!(0 T0, 1 T1, 2 T2, 3 T3, 4 T4, 5 T5);
+tuple_script_value!(0 T0, 1 T1, 2 T2, 3 T3, 4 T4, 5 T5, 6 T6);
+tuple_script_value!(0 T0, 1 T1, 2 T2, 3 T3, 4 T4, 5 T5, 6 T6, 7 T7);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -393,4 +829,74 @@ mod tests {
             assert_eq!(index as i32 + 1, val.to_int());
         });
     }
+
+    #[test]
+    fn test_numbers() {
+        let v = ScriptValue::new_double(-1.0);
+        assert!(matches!(
+            u32::from_script_value(&v),
+            Err(ScriptError::OutOfRange { target: "u32", .. })
+        ));
+        assert_eq!(Lossy::<u32>::from_script_value(&v), Ok(Lossy(u32::MAX)));
+        let v = ScriptValue::new_double(1.7);
+        assert!(matches!(
+            i32::from_script_value(&v),
+            Err(ScriptError::NonIntegral { .. })
+        ));
+        let v = ScriptValue::new_double(f64::NAN);
+        assert!(i32::from_script_value(&v).is_err());
+        assert!(f64::from_script_value(&v).unwrap().is_nan());
+        let v = ScriptValue::new_double(2f64.powi(63));
+        assert!(i64::from_script_value(&v).is_err());
+        assert!(u8::from_script_value(&ScriptValue::new_string("1")).is_err());
+    }
+
+    #[test]
+    fn test_collections() {
+        let v = ScriptValue::from(vec![Some(1u32), None]);
+        assert!(v.get_value_by_index(1).is_null());
+        assert_eq!(
+            Vec::<Option<u32>>::from_script_value(&v),
+            Ok(vec![Some(1), None])
+        );
+
+        let t = ScriptValue::from(("a", 'b', true));
+        assert_eq!(
+            <(String, char, bool)>::from_script_value(&t),
+            Ok(("a".to_string(), 'b', true))
+        );
+
+        let m: BTreeMap<String, Vec<u8>> = [("k".to_string(), vec![1, 2])].into();
+        let v = ScriptValue::from(m.clone());
+        assert_eq!(BTreeMap::from_script_value(&v), Ok(m));
+
+        let v = ScriptValue::from(vec![vec!["x"], vec![]]);
+        let err = Vec::<Vec<bool>>::from_script_value(&v).unwrap_err();
+        assert_eq!(err.path(), "[0][0]");
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn derive_default_field() {
+        #[derive(Debug, PartialEq, crate::FromScriptValue)]
+        struct Options {
+            name: String,
+            #[script(default)]
+            retries: u32,
+        }
+
+        let mut v = ScriptValue::new_object();
+        v.set_by_str("name", "a");
+        assert_eq!(
+            Options::from_script_value(&v),
+            Ok(Options {
+                name: "a".to_string(),
+                retries: 0
+            })
+        );
+        v.set_by_str("retries", ScriptValue::new_null());
+        assert_eq!(Options::from_script_value(&v).unwrap().retries, 0);
+        v.set_by_str("retries", 3);
+        assert_eq!(Options::from_script_value(&v).unwrap().retries, 3);
+    }
 }
@@ -0,0 +1,288 @@
+//! Timers running callbacks in main thread.
+//!
+//! A timer thread waits for the deadlines, and posts expired timers with
+//! `Application::run_in_main_thread`. Callbacks stay in main thread, they don't need to be `Send`.
+
+use crate::application::Application;
+use futures_core::Stream;
+use std::cell::{Cell, RefCell};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Condvar, Mutex, OnceLock};
+use std::task::{Context, Poll, Waker};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+static SCHEDULER: OnceLock<Scheduler> = OnceLock::new();
+
+struct Scheduler {
+    state: Mutex<SchedulerState>,
+    cond: Condvar,
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    queue: BinaryHeap<Reverse<(Instant, u64)>>,
+    thread: Option<JoinHandle<()>>,
+    stop: bool,
+}
+
+impl Scheduler {
+    fn get() -> &'static Scheduler {
+        SCHEDULER.get_or_init(|| Scheduler {
+            state: Mutex::new(SchedulerState::default()),
+            cond: Condvar::new(),
+        })
+    }
+    fn schedule(&'static self, deadline: Instant, id: u64) {
+        let mut state = self.state.lock().unwrap();
+        state.queue.push(Reverse((deadline, id)));
+        if state.thread.is_none() {
+            state.stop = false;
+            let thread = std::thread::Builder::new()
+                .name("kwui-timer".into())
+                .spawn(move || self.run())
+                .expect("spawn timer thread failed");
+            state.thread = Some(thread);
+        }
+        self.cond.notify_one();
+    }
+    fn run(&self) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if state.stop {
+                return;
+            }
+            let now = Instant::now();
+            match state.queue.peek() {
+                None => state = self.cond.wait(state).unwrap(),
+                Some(Reverse((deadline, _))) if *deadline > now => {
+                    let timeout = *deadline - now;
+                    state = self.cond.wait_timeout(state, timeout).unwrap().0;
+                }
+                Some(_) => {
+                    let Reverse((_, id)) = state.queue.pop().unwrap();
+                    // Don't block `schedule` while posting.
+                    drop(state);
+                    Application::run_in_main_thread(move || fire(id));
+                    state = self.state.lock().unwrap();
+                }
+            }
+        }
+    }
+    /// Clear the queue and stop the timer thread.
+    fn stop(&self) {
+        let thread = {
+            let mut state = self.state.lock().unwrap();
+            state.queue.clear();
+            state.stop = true;
+            state.thread.take()
+        };
+        self.cond.notify_one();
+        if let Some(thread) = thread {
+            let _ = thread.join();
+        }
+    }
+}
+
+struct Timer {
+    /// `None` while the callback is running.
+    callback: Option<Box<dyn FnMut()>>,
+    interval: Option<Duration>,
+    deadline: Instant,
+}
+
+thread_local! {
+    static TIMERS: RefCell<HashMap<u64, Timer>> = RefCell::new(HashMap::new());
+    static NEXT_ID: Cell<u64> = const { Cell::new(1) };
+}
+
+fn add_timer(delay: Duration, interval: Option<Duration>, callback: Box<dyn FnMut()>) -> u64 {
+    assert!(
+        Application::is_main_thread(),
+        "timers must be set in main thread"
+    );
+    let id = NEXT_ID.with(|next| next.replace(next.get() + 1));
+    let deadline = Instant::now() + delay;
+    TIMERS.with_borrow_mut(|timers| {
+        timers.insert(
+            id,
+            Timer {
+                callback: Some(callback),
+                interval,
+                deadline,
+            },
+        )
+    });
+    Scheduler::get().schedule(deadline, id);
+    id
+}
+
+fn fire(id: u64) {
+    let callback = TIMERS.with_borrow_mut(|timers| timers.get_mut(&id)?.callback.take());
+    // Cancelled, the callback may still be scheduled.
+    let Some(mut callback) = callback else {
+        return;
+    };
+    callback();
+    TIMERS.with_borrow_mut(|timers| {
+        // The callback may cancel its own timer.
+        let Some(timer) = timers.get_mut(&id) else {
+            return;
+        };
+        match timer.interval {
+            Some(interval) => {
+                // From the previous deadline, so the interval doesn't drift.
+                timer.callback = Some(callback);
+                timer.deadline += interval;
+                Scheduler::get().schedule(timer.deadline, id);
+            }
+            None => {
+                timers.remove(&id);
+            }
+        }
+    });
+}
+
+/// Drop all timers and stop the timer thread, when the `Application` quits.
+pub(crate) fn shutdown() {
+    if let Some(scheduler) = SCHEDULER.get() {
+        scheduler.stop();
+    }
+    TIMERS.with_borrow_mut(|timers| timers.clear());
+}
+
+/// A timer set by `Application::set_timeout` or `Application::set_interval`.
+///
+/// The timer is cancelled when the handle is dropped.
+#[must_use = "the timer is cancelled when the handle is dropped"]
+#[derive(Debug)]
+pub struct TimerHandle {
+    id: u64,
+    // Timers live in main thread.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl TimerHandle {
+    /// Cancel the timer, same as dropping the handle.
+    pub fn cancel(self) {}
+    /// Keep the timer running without the handle.
+    ///
+    /// A timeout fires once, an interval keeps firing until the application quits.
+    pub fn detach(self) {
+        std::mem::forget(self)
+    }
+}
+
+impl Drop for TimerHandle {
+    fn drop(&mut self) {
+        TIMERS.with_borrow_mut(|timers| timers.remove(&self.id));
+    }
+}
+
+#[derive(Debug, Default)]
+struct IntervalState {
+    ticks: u64,
+    waker: Option<Waker>,
+}
+
+/// Stream of interval ticks, made by `Application::interval`.
+///
+/// ```no_run
+/// use kwui::Application;
+/// use std::time::Duration;
+///
+/// Application::spawn_local(async {
+///     let mut interval = Application::interval(Duration::from_millis(100));
+///     for progress in 1..=10 {
+///         interval.tick().await;
+///         println!("progress {}0%", progress);
+///     }
+/// });
+/// ```
+#[derive(Debug)]
+pub struct Interval {
+    state: Rc<RefCell<IntervalState>>,
+    _timer: TimerHandle,
+}
+
+impl Interval {
+    /// Wait for the next tick.
+    pub async fn tick(&mut self) {
+        std::future::poll_fn(|cx| self.poll_tick(cx)).await
+    }
+    fn poll_tick(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.state.borrow_mut();
+        if state.ticks > 0 {
+            state.ticks -= 1;
+            Poll::Ready(())
+        } else {
+            state.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+impl Stream for Interval {
+    type Item = ();
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<()>> {
+        self.poll_tick(cx).map(Some)
+    }
+}
+
+impl Application {
+    /// Run `f` in main thread once, after `delay`.
+    ///
+    /// NOTE: panics if not called in main thread.
+    pub fn set_timeout<F: FnOnce() + 'static>(delay: Duration, f: F) -> TimerHandle {
+        let mut f = Some(f);
+        let callback = Box::new(move || {
+            if let Some(f) = f.take() {
+                f()
+            }
+        });
+        TimerHandle {
+            id: add_timer(delay, None, callback),
+            _not_send: PhantomData,
+        }
+    }
+    /// Run `f` in main thread every `period`.
+    ///
+    /// Ticks keep the schedule, a late tick doesn't delay the next ones.
+    ///
+    /// NOTE: panics if not called in main thread.
+    pub fn set_interval<F: FnMut() + 'static>(period: Duration, f: F) -> TimerHandle {
+        TimerHandle {
+            id: add_timer(period, Some(period), Box::new(f)),
+            _not_send: PhantomData,
+        }
+    }
+    /// Make a `Stream` ticking every `period`, for futures spawned by `spawn_local`.
+    ///
+    /// NOTE: panics if not called in main thread.
+    pub fn interval(period: Duration) -> Interval {
+        let state = Rc::new(RefCell::new(IntervalState::default()));
+        let timer = {
+            let state = state.clone();
+            Application::set_interval(period, move || {
+                let waker = {
+                    let mut state = state.borrow_mut();
+                    state.ticks += 1;
+                    state.waker.take()
+                };
+                // Waking may poll the interval immediately.
+                if let Some(waker) = waker {
+                    waker.wake();
+                }
+            })
+        };
+        Interval {
+            state,
+            _timer: timer,
+        }
+    }
+}
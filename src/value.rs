@@ -0,0 +1,265 @@
+use crate::script_error::ScriptError;
+use crate::script_value::{FromScriptValue, IntoScriptValue, ScriptValue};
+use std::collections::BTreeMap;
+
+/// An owned JavaScript value, detached from the script engine.
+///
+/// `ScriptValue` is a handle into the script engine, and must be used in main thread.
+/// `Value` owns its data, so it can be built in worker threads, then sent to main thread
+/// and converted to `ScriptValue`.
+///
+/// ```no_run
+/// use kwui::{Application, ScriptEngine, Value};
+///
+/// std::thread::spawn(|| {
+///     let payload = Value::from(vec![Value::from("a"), Value::from(1)]);
+///     Application::run_in_main_thread(move || {
+///         ScriptEngine::post_event1("worker:done", payload);
+///     });
+/// });
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Value {
+    #[default]
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(BTreeMap<String, Value>),
+}
+
+impl Value {
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(v) => Some(*v),
+            _ => None,
+        }
+    }
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(v) => Some(v),
+            _ => None,
+        }
+    }
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+    pub fn as_object(&self) -> Option<&BTreeMap<String, Value>> {
+        match self {
+            Value::Object(v) => Some(v),
+            _ => None,
+        }
+    }
+    /// Retrieve sub-element at `idx` of an array value.
+    pub fn get_by_index(&self, idx: usize) -> Option<&Value> {
+        self.as_array().and_then(|arr| arr.get(idx))
+    }
+    /// Retrieve sub-element at `key` of an object value.
+    pub fn get_by_str(&self, key: &str) -> Option<&Value> {
+        self.as_object().and_then(|obj| obj.get(key))
+    }
+}
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Self {
+        Value::Bool(v)
+    }
+}
+
+macro_rules! value_from_number {
+    ($($ty:ident),*) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(v: $ty) -> Self {
+                    Value::Number(v as _)
+                }
+            }
+        )*
+    };
+}
+value_from_number!(i8, u8, i16, u16, i32, u32, f32, f64);
+
+impl From<&str> for Value {
+    fn from(v: &str) -> Self {
+        Value::String(v.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(v: String) -> Self {
+        Value::String(v)
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(v: Vec<Value>) -> Self {
+        Value::Array(v)
+    }
+}
+
+impl From<BTreeMap<String, Value>> for Value {
+    fn from(v: BTreeMap<String, Value>) -> Self {
+        Value::Object(v)
+    }
+}
+
+impl From<()> for Value {
+    fn from(_: ()) -> Self {
+        Value::Null
+    }
+}
+
+impl From<&Value> for ScriptValue {
+    fn from(v: &Value) -> Self {
+        match v {
+            Value::Null => ScriptValue::new_null(),
+            Value::Bool(v) => ScriptValue::new_bool(*v),
+            Value::Number(v) => ScriptValue::new_double(*v),
+            Value::String(v) => ScriptValue::new_string(v),
+            Value::Array(v) => {
+                let arr = ScriptValue::new_array();
+                for (i, v) in v.iter().enumerate() {
+                    arr.set_value_by_index(i, v.into());
+                }
+                arr
+            }
+            Value::Object(v) => {
+                let mut obj = ScriptValue::new_object();
+                for (k, v) in v.iter() {
+                    obj.set_value_by_str(k, v.into());
+                }
+                obj
+            }
+        }
+    }
+}
+
+impl From<Value> for ScriptValue {
+    fn from(v: Value) -> Self {
+        (&v).into()
+    }
+}
+
+impl TryFrom<&ScriptValue> for Value {
+    type Error = ScriptError;
+
+    fn try_from(v: &ScriptValue) -> Result<Self, ScriptError> {
+        if v.is_null() {
+            Ok(Value::Null)
+        } else if v.is_bool() {
+            Ok(Value::Bool(v.to_bool()))
+        } else if v.is_number() {
+            Ok(Value::Number(v.to_double()))
+        } else if v.is_string() {
+            Ok(Value::String(v.to_string()))
+        } else if v.is_array() {
+            let mut arr = Vec::with_capacity(v.length());
+            let mut ret = Ok(());
+            v.visit_array(|i, v| {
+                if ret.is_ok() {
+                    ret = Value::try_from(v)
+                        .map(|v| arr.push(v))
+                        .map_err(|e| e.at_index(i));
+                }
+            });
+            ret.map(|_| Value::Array(arr))
+        } else if v.is_object() {
+            let mut obj = BTreeMap::new();
+            let mut ret = Ok(());
+            v.visit_object(|k, v| {
+                if ret.is_ok() {
+                    ret = Value::try_from(v)
+                        .map(|v| {
+                            obj.insert(k.to_string(), v);
+                        })
+                        .map_err(|e| e.at_key(k));
+                }
+            });
+            ret.map(|_| Value::Object(obj))
+        } else {
+            Err(ScriptError::type_mismatch("value", v))
+        }
+    }
+}
+
+impl TryFrom<ScriptValue> for Value {
+    type Error = ScriptError;
+
+    fn try_from(v: ScriptValue) -> Result<Self, ScriptError> {
+        Value::try_from(&v)
+    }
+}
+
+impl FromScriptValue for Value {
+    fn from_script_value(value: &ScriptValue) -> Result<Self, ScriptError> {
+        Value::try_from(value)
+    }
+}
+
+impl IntoScriptValue for Value {
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
+        Ok(self.into())
+    }
+}
+
+impl IntoScriptValue for &Value {
+    fn into_script_value(self) -> Result<ScriptValue, ScriptError> {
+        Ok(self.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn value_is_thread_safe() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Value>();
+
+        let v = std::thread::spawn(|| {
+            let mut obj = BTreeMap::new();
+            obj.insert("title".to_string(), Value::from("a"));
+            obj.insert(
+                "items".to_string(),
+                Value::from(vec![1.into(), true.into()]),
+            );
+            Value::from(obj)
+        })
+        .join()
+        .unwrap();
+        assert_eq!(v.get_by_str("title").and_then(Value::as_str), Some("a"));
+        assert_eq!(
+            v.get_by_str("items").and_then(|v| v.get_by_index(1)),
+            Some(&Value::Bool(true))
+        );
+    }
+
+    #[test]
+    fn script_value_round_trip() {
+        let mut obj = BTreeMap::new();
+        obj.insert("n".to_string(), Value::Number(1.5));
+        obj.insert("s".to_string(), Value::from("text"));
+        obj.insert(
+            "a".to_string(),
+            Value::Array(vec![Value::Null, Value::Bool(false)]),
+        );
+        let v = Value::Object(obj);
+
+        let sv = ScriptValue::from(&v);
+        assert_eq!(Value::try_from(&sv), Ok(v));
+    }
+}